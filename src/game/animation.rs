@@ -5,7 +5,7 @@ use crate::game::map::tile_effects::highlight_tiles_for_effect;
 use crate::network::send::send_card_selection;
 use crate::game::cards::card::Card;
 use crate::game::map::tile::Direction;
-use crate::network::send::send_fireball_hit;
+use crate::network::receive::apply_fireball_hit_result;
 
 #[derive(
     Clone,
@@ -94,6 +94,24 @@ pub struct AnimatedFireball {
     pub direction: Direction,
     pub animating: bool,
     pub current_tile_index: usize, // which tile we're currently in
+    pub end_tile_index: usize, // tile the server decided the fireball stops on
+    pub hits_target: bool, // stop at the tile center (hit) instead of the far wall
+}
+
+/// A fireball hit result from the server, held until the fireball animation reaches its target
+#[derive(
+    Clone,
+    Debug,
+    borsh::BorshDeserialize,
+    borsh::BorshSerialize,
+    serde::Serialize,
+    serde::Deserialize
+)]
+pub struct PendingFireballHit {
+    pub player_id: String,
+    pub target_id: String,
+    pub damage_dealt: u32,
+    pub monster_damage: Option<u32>,
 }
 
 pub fn update_animations(state: &mut GameState) {
@@ -171,7 +189,7 @@ pub fn handle_animated_card_complete(state: &mut crate::GameState) {
             state.selected_card = Some(drag.card.clone());
             highlight_selected_card_tiles(state);
             if let Some(hand_index) = drag.card.hand_index {
-                send_card_selection(hand_index);
+                send_card_selection(&state.room_id, hand_index);
             }
         } else if drag.target_row == AnimatedCardOrigin::Hand {
             // Card is returning to hand from play area - restore it to the hand
@@ -191,7 +209,8 @@ pub fn highlight_selected_card_tiles(state: &mut GameState) {
     clear_highlights(&mut state.tiles);
     if let Some(card) = &selected_card {
         if let Some(player) = state.get_local_player() {
-            highlight_tiles_for_effect(&card.effect, player.position, &mut state.tiles);
+            let position = player.position;
+            highlight_tiles_for_effect(&card.effect, position, &mut state.tiles, state.board_size);
         }
     }
 }
@@ -250,11 +269,11 @@ pub fn start_player_movement_animation(
     let from_screen_y = offset_y + (from_pos.1 as u32) * tile_size + tile_size / 2;
 
     // Find the path from start to target
-    let start_index = Tile::index(from_pos.0, from_pos.1);
-    let target_index = Tile::index(to_pos.0, to_pos.1);
-    let path = Tile::find_walkable_path(start_index, target_index, &state.tiles).unwrap_or_else(||
-        vec![start_index, target_index]
-    );
+    let size = state.board_size;
+    let start_index = size.index(from_pos.0, from_pos.1);
+    let target_index = size.index(to_pos.0, to_pos.1);
+    let path = Tile::find_walkable_path(start_index, target_index, &state.tiles, size)
+        .unwrap_or_else(|| vec![start_index, target_index]);
 
     state.animated_player = Some(AnimatedPlayer {
         player_id: player_id.to_string(),
@@ -288,8 +307,8 @@ pub fn start_direct_player_movement_animation(
     let from_screen_y = offset_y + (from_pos.1 as u32) * tile_size + tile_size / 2;
 
     // Use direct path (no pathfinding)
-    let start_index = Tile::index(from_pos.0, from_pos.1);
-    let target_index = Tile::index(to_pos.0, to_pos.1);
+    let start_index = state.board_size.index(from_pos.0, from_pos.1);
+    let target_index = state.board_size.index(to_pos.0, to_pos.1);
     let path = vec![start_index, target_index];
 
     state.animated_player = Some(AnimatedPlayer {
@@ -313,6 +332,7 @@ pub fn start_direct_player_movement_animation(
 pub fn update_player_movement_animations(state: &mut GameState) {
     // Get board layout before mutable borrow
     let (_, _, tile_size, offset_x, offset_y) = state.get_board_layout(false);
+    let size = state.board_size;
 
     let mut direction_update: Option<(String, crate::game::map::player::Direction)> = None;
 
@@ -321,7 +341,7 @@ pub fn update_player_movement_animations(state: &mut GameState) {
             // Get current target from path
             if anim.current_path_index < anim.path.len() {
                 let current_target_index = anim.path[anim.current_path_index];
-                let (target_x, target_y) = Tile::position(current_target_index);
+                let (target_x, target_y) = size.position(current_target_index);
                 let target_screen_x = offset_x + (target_x as u32) * tile_size + tile_size / 2;
                 let target_screen_y = offset_y + (target_y as u32) * tile_size + tile_size / 2;
                 let target_pos = (target_screen_x as f32, target_screen_y as f32);
@@ -402,7 +422,13 @@ pub fn animate_tile_to_index(state: &mut GameState, tile_index: usize, target_in
     let (_, _, tile_size, offset_x, offset_y) = state.get_board_layout(false);
 
     // Calculate current screen position
-    let (from_x, from_y) = Tile::screen_position(tile_index, tile_size, offset_x, offset_y);
+    let (from_x, from_y) = Tile::screen_position(
+        tile_index,
+        state.board_size,
+        tile_size,
+        offset_x,
+        offset_y
+    );
 
     // Create new animated tile
     let animated_tile = AnimatedTile {
@@ -421,6 +447,7 @@ pub fn animate_tile_to_index(state: &mut GameState, tile_index: usize, target_in
 pub fn update_tile_animations(state: &mut GameState) {
     // Get board layout before mutable borrow
     let (_, _, tile_size, offset_x, offset_y) = state.get_board_layout(false);
+    let size = state.board_size;
 
     // Update each animated tile
     let mut completed_indices = Vec::new();
@@ -430,6 +457,7 @@ pub fn update_tile_animations(state: &mut GameState) {
             // Calculate target screen position
             let (target_x, target_y) = Tile::screen_position(
                 anim.target_index,
+                size,
                 tile_size,
                 offset_x,
                 offset_y
@@ -487,21 +515,30 @@ pub fn start_fireball_animation(
     fireball_id: u32,
     start_pos: (usize, usize),
     direction: Direction,
-    _target_tile: usize
+    end_tile_index: usize,
+    hits_target: bool
 ) {
     log!(
-        "🔥 [ANIMATION] Starting fireball animation: id={}, start={:?}, direction={:?}",
+        "🔥 [ANIMATION] Starting fireball animation: id={}, start={:?}, direction={:?}, end={}",
         fireball_id,
         start_pos,
-        direction
+        direction,
+        end_tile_index
     );
 
     // Get board layout for initial position calculation
     let (_, _, tile_size, offset_x, offset_y) = state.get_board_layout(false);
 
     // Calculate starting screen position (center of starting tile)
-    let start_tile_index = Tile::index(start_pos.0, start_pos.1);
-    let (start_x, start_y) = Tile::screen_position(start_tile_index, tile_size, offset_x, offset_y);
+    let size = state.board_size;
+    let start_tile_index = size.index(start_pos.0, start_pos.1);
+    let (start_x, start_y) = Tile::screen_position(
+        start_tile_index,
+        size,
+        tile_size,
+        offset_x,
+        offset_y
+    );
     let start_screen_pos = (
         (start_x as f32) + (tile_size as f32) / 2.0,
         (start_y as f32) + (tile_size as f32) / 2.0,
@@ -514,24 +551,23 @@ pub fn start_fireball_animation(
         direction,
         animating: true,
         current_tile_index: start_tile_index,
+        end_tile_index,
+        hits_target,
     };
 
     state.animated_fireballs.push(animated_fireball);
 }
 
 /// Update all fireball animations
+/// The server has already decided where each fireball stops, so this only moves the sprite
+/// until it reaches that tile and then applies any hit result the server sent.
 pub fn update_fireball_animations(state: &mut GameState) {
     let mut completed_indices = Vec::new();
     let mut fireballs_to_deactivate = Vec::new();
 
     // Get board layout for position calculations
     let (_, _, tile_size, offset_x, offset_y) = state.get_board_layout(false);
-
-    // Get local player ID before the loop to avoid borrowing issues
-    let local_player_id = state.get_local_player().map(|p| p.id.clone());
-    let local_player_tile_index = state
-        .get_local_player()
-        .map(|p| Tile::index(p.position.0, p.position.1));
+    let size = state.board_size;
 
     for (i, anim) in state.animated_fireballs.iter_mut().enumerate() {
         if anim.animating {
@@ -546,143 +582,46 @@ pub fn update_fireball_animations(state: &mut GameState) {
                 Direction::Right => (x + speed, y),
             };
 
-            // Check if we hit a wall or player using the cleaner helper approach
             let fireball_radius = (tile_size as f32) / 8.0; // tile_size / 4 / 2
 
-            // First check if we hit a player (excluding the shooter)
-            let player_hit = {
-                let shooter_id = if
-                    let Some(fireball) = state.fireballs.iter().find(|f| f.id == anim.fireball_id)
-                {
-                    fireball.shooter_id.clone()
-                } else {
-                    continue; // Skip if fireball not found
-                };
-
-                // Get positions of all players except the shooter
-                let other_player_positions: Vec<_> = state.players
-                    .iter()
-                    .enumerate()
-                    .filter(|(_, p)| p.id != shooter_id)
-                    .map(|(_, p)| p.position)
-                    .collect();
-
-                Tile::would_fireball_hit_player(
-                    new_pos,
-                    fireball_radius,
-                    &other_player_positions,
+            let reached_end = if anim.current_tile_index != anim.end_tile_index {
+                false
+            } else if anim.hits_target {
+                // Stop once the fireball reaches the center of the target's tile
+                let (tx, ty) = Tile::screen_position(
+                    anim.end_tile_index,
+                    size,
                     tile_size,
                     offset_x,
                     offset_y
-                )
-            };
-
-            // Check if we hit the monster
-            let monster_hit = if let Some(monster) = &state.monster {
-                if monster.is_alive() {
-                    // Convert monster position to screen coordinates
-                    let monster_screen_x =
-                        offset_x + (monster.position.0 as u32) * tile_size + tile_size / 2;
-                    let monster_screen_y =
-                        offset_y + (monster.position.1 as u32) * tile_size + tile_size / 2;
-                    let monster_pos = (monster_screen_x as f32, monster_screen_y as f32);
-
-                    // Check if fireball hits monster (using same logic as player hit)
-                    let dx = new_pos.0 - monster_pos.0;
-                    let dy = new_pos.1 - monster_pos.1;
-                    let distance = (dx * dx + dy * dy).sqrt();
-
-                    if distance < fireball_radius + (tile_size as f32) / 4.0 {
-                        Some(monster.position)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
+                );
+                let center_x = (tx as f32) + (tile_size as f32) / 2.0;
+                let center_y = (ty as f32) + (tile_size as f32) / 2.0;
+                match anim.direction {
+                    Direction::Up => new_pos.1 <= center_y,
+                    Direction::Down => new_pos.1 >= center_y,
+                    Direction::Left => new_pos.0 <= center_x,
+                    Direction::Right => new_pos.0 >= center_x,
                 }
             } else {
-                None
-            };
-
-            let hit_wall = if player_hit.is_some() || monster_hit.is_some() {
-                false // Player or monster hit takes priority over wall hit
-            } else {
-                let current_tile = &state.tiles[anim.current_tile_index];
-
-                // Check if we've reached the far edge of the current tile (minus fireball radius)
-                let reached_far_edge = Tile::has_fireball_reached_far_edge(
-                    anim.current_tile_index,
+                // Stop once the fireball reaches the wall at the far edge of the tile
+                Tile::has_fireball_reached_far_edge(
+                    size.position(anim.current_tile_index),
                     anim.direction,
                     new_pos,
                     fireball_radius,
                     tile_size,
                     offset_x,
                     offset_y
-                );
-
-                if reached_far_edge {
-                    // Use the helper function to check if we'd hit a wall
-                    current_tile.would_fireball_hit_wall(
-                        anim.current_tile_index,
-                        anim.direction,
-                        &state.tiles
-                    )
-                } else {
-                    false // Still within current tile, no wall hit
-                }
+                )
             };
 
-            if hit_wall || player_hit.is_some() || monster_hit.is_some() {
-                if let Some(player_index) = player_hit {
-                    // Only send the hit to the server if I'm the one who shot this fireball
-                    if
-                        let Some(fireball) = state.fireballs
-                            .iter()
-                            .find(|f| f.id == anim.fireball_id)
-                    {
-                        // Check if the local player is the shooter
-                        if let Some(ref local_id) = local_player_id {
-                            if fireball.shooter_id == *local_id {
-                                let player_tile_index = local_player_tile_index;
-
-                                if let Some(player_tile_index) = player_tile_index {
-                                    log!(
-                                        "🔥 [ANIMATION] Fireball {} hit player {} at {:?}",
-                                        anim.fireball_id,
-                                        player_index,
-                                        player_tile_index
-                                    );
-                                    send_fireball_hit(state.user.clone(), player_tile_index);
-                                }
-                            }
-                        }
-                    }
-                }
-
-                if let Some(monster_pos) = monster_hit {
-                    let monster_tile_index = Tile::index(monster_pos.0, monster_pos.1);
-
-                    log!(
-                        "🔥 [ANIMATION] Fireball {} hit monster at {:?}",
-                        anim.fireball_id,
-                        monster_tile_index
-                    );
-
-                    // Send FireballHit message to server for monster damage
-                    if
-                        let Some(fireball) = state.fireballs
-                            .iter()
-                            .find(|f| f.id == anim.fireball_id)
-                    {
-                        // Check if the local player is the shooter
-                        if let Some(ref local_id) = local_player_id {
-                            if fireball.shooter_id == *local_id {
-                                send_fireball_hit(state.user.clone(), monster_tile_index);
-                            }
-                        }
-                    }
-                }
-
+            if reached_end {
+                log!(
+                    "🔥 [ANIMATION] Fireball {} reached tile {}",
+                    anim.fireball_id,
+                    anim.end_tile_index
+                );
                 completed_indices.push(i);
                 fireballs_to_deactivate.push(anim.fireball_id);
             } else {
@@ -698,15 +637,17 @@ pub fn update_fireball_animations(state: &mut GameState) {
                     // Convert screen position back to tile position for the fireball struct
                     let tile_x = ((new_pos.0 - (offset_x as f32)) / (tile_size as f32)) as usize;
                     let tile_y = ((new_pos.1 - (offset_y as f32)) / (tile_size as f32)) as usize;
-                    fireball.position = (tile_x.min(4), tile_y.min(4)); // Clamp to map bounds
+                    // Clamp to map bounds
+                    fireball.position = (tile_x.min(size.width - 1), tile_y.min(size.height - 1));
                 }
 
                 // Check if we've moved to a new tile and update current_tile_index
                 let new_tile_x = ((new_pos.0 - (offset_x as f32)) / (tile_size as f32)) as usize;
                 let new_tile_y = ((new_pos.1 - (offset_y as f32)) / (tile_size as f32)) as usize;
-                let new_tile_index = new_tile_x + new_tile_y * 5;
+                let new_tile_index = size.index(new_tile_x, new_tile_y);
 
-                if new_tile_index != anim.current_tile_index && new_tile_index < 25 {
+                let is_on_board = size.contains((new_tile_x, new_tile_y));
+                if new_tile_index != anim.current_tile_index && is_on_board {
                     anim.current_tile_index = new_tile_index;
                 }
             }
@@ -724,6 +665,22 @@ pub fn update_fireball_animations(state: &mut GameState) {
     for &index in completed_indices.iter().rev() {
         state.animated_fireballs.remove(index);
     }
+
+    // Apply the server's hit result once the fireball has landed
+    let landed_hit = if state.animated_fireballs.is_empty() {
+        state.pending_fireball_hit.take()
+    } else {
+        None
+    };
+    if let Some(hit) = landed_hit {
+        apply_fireball_hit_result(
+            state,
+            &hit.player_id,
+            &hit.target_id,
+            &hit.damage_dealt,
+            hit.monster_damage
+        );
+    }
 }
 
 /// Updates player sprite animations (walking frames, etc.)
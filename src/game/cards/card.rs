@@ -1,4 +1,5 @@
 use crate::game::cards::card_effect::CardEffect;
+use crate::rules::Rng;
 use turbo::{ borsh::{ BorshDeserialize, BorshSerialize }, * };
 use serde::{ Serialize, Deserialize };
 use crate::game::constants::{
@@ -34,7 +35,7 @@ pub struct Card {
     pub sprite_name: String, // Name of the sprite file (without extension)
 }
 
-const CARD_CONSTRUCTORS: &[fn() -> Card] = &[
+const CARD_CONSTRUCTORS: &[fn(u32) -> Card] = &[
     Card::move_card,
     Card::rotate_card,
     Card::swap_card,
@@ -50,18 +51,28 @@ impl Card {
         }
     }
 
-    pub fn random() -> Self {
-        let index = (random::u32() as usize) % CARD_CONSTRUCTORS.len();
-        (CARD_CONSTRUCTORS[index])()
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let index = rng.pick(CARD_CONSTRUCTORS.len());
+        (CARD_CONSTRUCTORS[index])(Self::new_id(rng))
     }
 
-    pub fn get_unique_cards() -> Vec<Self> {
-        vec![Self::move_card(), Self::rotate_card(), Self::swap_card(), Self::fire_card()]
+    pub fn get_unique_cards(rng: &mut impl Rng) -> Vec<Self> {
+        vec![
+            Self::move_card(Self::new_id(rng)),
+            Self::rotate_card(Self::new_id(rng)),
+            Self::swap_card(Self::new_id(rng)),
+            Self::fire_card(Self::new_id(rng))
+        ]
     }
 
-    pub fn rotate_card() -> Self {
+    /// A random card id; 0 is kept for dummy cards
+    fn new_id(rng: &mut impl Rng) -> u32 {
+        rng.next_u32().max(1)
+    }
+
+    pub fn rotate_card(id: u32) -> Self {
         Self {
-            id: random::u32(),
+            id,
             name: "TURN".into(),
             effect: CardEffect::RotateCard,
             color: CARD_ROTATE_COLOR,
@@ -72,9 +83,9 @@ impl Card {
         }
     }
 
-    pub fn move_card() -> Self {
+    pub fn move_card(id: u32) -> Self {
         Self {
-            id: random::u32(),
+            id,
             name: "MOVE".into(),
             effect: CardEffect::MoveOneTile,
             color: CARD_MOVE_COLOR,
@@ -85,9 +96,9 @@ impl Card {
         }
     }
 
-    pub fn swap_card() -> Self {
+    pub fn swap_card(id: u32) -> Self {
         Self {
-            id: random::u32(),
+            id,
             name: "SWAP".into(),
             effect: CardEffect::SwapCard,
             color: CARD_SWAP_COLOR,
@@ -98,9 +109,9 @@ impl Card {
         }
     }
 
-    pub fn fire_card() -> Self {
+    pub fn fire_card(id: u32) -> Self {
         Self {
-            id: random::u32(),
+            id,
             name: "FIRE".into(),
             effect: CardEffect::FireCard,
             color: CARD_FIRE_COLOR,
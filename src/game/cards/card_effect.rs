@@ -1,13 +1,11 @@
-use crate::game::constants::{ DEBUG_MODE, MAP_SIZE };
+use crate::game::constants::DEBUG_MODE;
 use crate::game::map::Tile;
 use crate::game::animation::{
     start_tile_rotation_animation,
     start_player_movement_animation,
-    start_fireball_animation,
 };
 use crate::GameState;
 use crate::network::send::{ send_tile_rotation, send_move, send_swap_tiles, send_fireball_shot };
-use crate::game::map::fireball::Fireball;
 use crate::game::map::clear_highlights;
 use crate::game::animation::animate_tile_to_index;
 use turbo::*;
@@ -53,7 +51,7 @@ impl CardEffect {
         let current_position = player.position;
         let user_id = state.user.clone();
 
-        let current_index = py * MAP_SIZE + px;
+        let current_index = state.board_size.index(px, py);
 
         if tile_index == current_index {
             return;
@@ -71,9 +69,7 @@ impl CardEffect {
         }
 
         // Calculate new position from tile index
-        let new_x = tile_index % MAP_SIZE;
-        let new_y = tile_index / MAP_SIZE;
-        let new_position = (new_x, new_y);
+        let new_position = state.board_size.position(tile_index);
 
         // Start local player movement animation immediately
         start_player_movement_animation(
@@ -86,14 +82,14 @@ impl CardEffect {
             offset_y
         );
 
-        send_move(new_position, false);
+        send_move(&state.room_id, new_position, false);
     }
 
     fn apply_rotate_card(&self, state: &mut GameState, tile_index: usize) {
         let tile = &mut state.tiles[tile_index];
         if tile.is_highlighted {
             if tile.rotation_anim.is_none() {
-                send_tile_rotation(tile_index);
+                send_tile_rotation(&state.room_id, tile_index);
             }
             start_tile_rotation_animation(state, tile_index, 0.25);
         }
@@ -118,7 +114,7 @@ impl CardEffect {
         if state.swap_tiles_selected.len() == 2 {
             let tile1 = state.swap_tiles_selected[0];
             let tile2 = state.swap_tiles_selected[1];
-            send_swap_tiles(tile1, tile2);
+            send_swap_tiles(&state.room_id, tile1, tile2);
             state.swap_tiles_selected.clear();
         }
     }
@@ -128,7 +124,7 @@ impl CardEffect {
         if let Some(local_player) = state.get_local_player() {
             let player_pos = local_player.position;
             let tile = &state.tiles[tile_index];
-            let (target_x, target_y) = Tile::position(tile_index);
+            let (target_x, target_y) = state.board_size.position(tile_index);
 
             if tile.is_highlighted {
                 // Calculate direction from player to target tile
@@ -142,13 +138,8 @@ impl CardEffect {
                     crate::game::map::tile::Direction::Up
                 };
 
-                // Create fireball at player position
-                let fireball = Fireball::new(10, player_pos, direction, local_player.id.clone());
-                let fireball_id = fireball.id;
-                state.fireballs.push(fireball);
-
-                start_fireball_animation(state, fireball_id, player_pos, direction, tile_index);
-                send_fireball_shot(tile_index, direction);
+                // The server traces the fireball and broadcasts where it lands
+                send_fireball_shot(&state.room_id, tile_index, direction);
 
                 if !DEBUG_MODE {
                     // Clear highlights and selected card
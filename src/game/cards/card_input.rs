@@ -162,7 +162,7 @@ pub fn handle_card_cancel(state: &mut GameState, play_area_idx: usize, selected:
     if let Some(original_hand_idx) = selected.hand_index {
         if let Some(player) = state.get_local_player_mut() {
             if original_hand_idx < player.hand.len() {
-                send_card_cancel(original_hand_idx);
+                send_card_cancel(&state.room_id, original_hand_idx);
 
                 // Clear the play area slot first
                 state.play_area[play_area_idx] = Card::dummy_card();
@@ -192,7 +192,9 @@ pub fn handle_card_cancel(state: &mut GameState, play_area_idx: usize, selected:
 
 pub fn confirm_card(state: &mut GameState) {
     if let Some(selected_card) = &state.selected_card {
-        send_confirm_card(selected_card);
+        if let Some(hand_index) = selected_card.hand_index {
+            send_confirm_card(&state.room_id, hand_index);
+        }
         state.selected_card = None;
         clear_highlights(&mut state.tiles);
     }
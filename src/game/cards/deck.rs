@@ -0,0 +1,48 @@
+use crate::game::cards::card::Card;
+use crate::game::constants::DECK_COPIES;
+use crate::rules::Rng;
+use serde::{ Serialize, Deserialize };
+use borsh::{ BorshSerialize, BorshDeserialize };
+
+/// A player's own cards outside their hand. Played cards go on the discard pile, which is
+/// shuffled back in once the draw pile runs out. Only kept on the server; clients are told
+/// how many cards each pile holds.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Deck {
+    draw_pile: Vec<Card>, // Drawn from the back
+    discard_pile: Vec<Card>,
+}
+
+impl Deck {
+    /// A shuffled starting deck with a few copies of every card
+    pub fn new(rng: &mut impl Rng) -> Self {
+        let mut draw_pile: Vec<Card> = (0..DECK_COPIES)
+            .flat_map(|_| Card::get_unique_cards(rng))
+            .collect();
+        rng.shuffle(&mut draw_pile);
+        Self { draw_pile, discard_pile: Vec::new() }
+    }
+
+    /// Takes the top card, reshuffling the discard pile into the draw pile when it is empty.
+    /// None once both piles are empty.
+    pub fn draw(&mut self, rng: &mut impl Rng) -> Option<Card> {
+        if self.draw_pile.is_empty() {
+            self.draw_pile.append(&mut self.discard_pile);
+            rng.shuffle(&mut self.draw_pile);
+        }
+        self.draw_pile.pop()
+    }
+
+    pub fn discard(&mut self, mut card: Card) {
+        card.hand_index = None;
+        self.discard_pile.push(card);
+    }
+
+    pub fn draw_count(&self) -> usize {
+        self.draw_pile.len()
+    }
+
+    pub fn discard_count(&self) -> usize {
+        self.discard_pile.len()
+    }
+}
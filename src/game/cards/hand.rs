@@ -1,5 +1,5 @@
 use crate::game::cards::card::{ Card, CardVisualState };
-use crate::game::constants::{ GAME_PADDING, HAND_SIZE, MAP_SIZE };
+use crate::game::constants::{ GAME_PADDING, HAND_SIZE };
 use turbo::*;
 use crate::GameState;
 use crate::game::cards::card_row::CardRow;
@@ -11,11 +11,10 @@ pub fn get_card_sizes(canvas_width: u32, canvas_height: u32) -> (u32, u32) {
     (card_width, card_height)
 }
 
+/// The hand sits below the board's square, whatever the shape of the board inside it
 pub fn get_hand_y() -> u32 {
     let canvas_bounds = bounds::screen();
-    let canvas_width = canvas_bounds.w();
-    let tile_size = canvas_width / (MAP_SIZE as u32);
-    tile_size * (MAP_SIZE as u32) + GAME_PADDING
+    canvas_bounds.w() + GAME_PADDING
 }
 
 pub fn get_card_position(index: usize, card_width: u32) -> (u32, u32) {
@@ -47,7 +46,8 @@ pub fn hovered_card_index(
 }
 
 pub fn draw_hand(state: &GameState, frame: f64) {
-    let hand = &state.get_local_player().unwrap().hand;
+    let player = state.get_local_player().unwrap();
+    let hand = &player.hand;
     if hand.is_empty() {
         return;
     }
@@ -87,6 +87,16 @@ pub fn draw_hand(state: &GameState, frame: f64) {
     }
     row.draw(frame);
 
+    // The player's piles, in the gap between the hand and the play area
+    let piles = format!("DECK {}   DISCARD {}", player.draw_pile_count, player.discard_pile_count);
+    text!(
+        &piles,
+        x = GAME_PADDING as i32,
+        y = (y + card_height + GAME_PADDING / 4) as i32,
+        font = "small",
+        color = 0xffffffaa
+    );
+
     // Draw tooltip if hovering over a card and no card is being animated
     if let Some(hovered_index) = hovered {
         if let Some(card) = hand.get(hovered_index) {
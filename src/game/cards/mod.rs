@@ -3,6 +3,7 @@ pub mod card_buttons;
 pub mod card_effect;
 pub mod card_row;
 pub mod card_slot;
+pub mod deck;
 pub mod hand;
 pub mod play_area;
 pub mod card_input;
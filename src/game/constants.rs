@@ -1,14 +1,27 @@
 pub const DEBUG_MODE: bool = false;
 
 // Game
-pub const MAP_SIZE: usize = 5;
+pub const BOARD_SIZES: [(usize, usize); 4] = [(5, 5), (6, 6), (7, 5), (5, 7)]; // First is default
+pub const MIN_BOARD_SIDE: usize = 3; // Room for the corners and a centre the monster spawns on
+pub const MAX_BOARD_SIDE: usize = 9;
+pub const KEY_COUNT: usize = 2; // Keys lying on the board at the start of a match
+pub const LOCKED_DOOR_COUNT: usize = 2; // One key opens one door
+pub const LAVA_TILE_COUNT: usize = 2;
+pub const FOUNTAIN_TILE_COUNT: usize = 1;
+pub const PORTAL_PAIR_COUNT: usize = 1;
+pub const TREASURE_TILE_COUNT: usize = 1;
 pub const HAND_SIZE: usize = 4;
+pub const DECK_COPIES: usize = 3; // Copies of each card in a player's starting deck
+pub const MIN_PLAYERS: usize = 2; // Players per room
+pub const MAX_PLAYERS: usize = 4;
 
 // UI
 pub const FONT_HEIGHT: u32 = 12;
 pub const GAME_PADDING: u32 = 16;
 pub const POSITIVE_BG_COLOR: u32 = 0x119911ff;
 pub const NEGATIVE_BG_COLOR: u32 = 0x991111ff;
+pub const LOCKED_DOOR_COLOR: u32 = 0x8b5a2bff;
+pub const PORTAL_PAIR_COLORS: [u32; 3] = [0x9b59ffff, 0x33ccffff, 0xff66ccff]; // Told apart by tint
 
 // Tile random generation
 pub const ENTRANCE_COUNT_WEIGHT_1: f32 = 1.0;
@@ -32,10 +45,24 @@ pub const CARD_BUTTON_B_COLOR: u32 = 0xff2222ff;
 
 // Animation
 pub const FLASH_SPEED: f64 = 0.1; // smaller = slower
-pub const GAME_CHANNEL: &str = "GLOBAL";
+
+// Network
+pub const PROTOCOL_VERSION: u32 = 16; // Bump on any change to the types in protocol/
+pub const CLIENT_BUILD: &str = env!("CARGO_PKG_VERSION"); // Reported in Hello for the server logs
+pub const HELLO_RETRY_FRAMES: usize = 60; // Hello is resent until the server answers
+pub const LOBBY_CHANNEL: &str = "LOBBY";
+pub const ROOM_CODE_LENGTH: usize = 5;
+pub const MAX_SEED_DIGITS: usize = 20; // Enough for any u64
+pub const ROOM_CODE_CHARS: &str = "ABCDEFGHJKLMNPQRSTUVWXYZ23456789"; // No 0/O or 1/I look-alikes
+pub const RECONNECT_GRACE_MS: u64 = 60_000; // How long a dropped player's seat is held
+pub const RECONNECT_CHECK_INTERVAL_MS: u32 = 1000;
+pub const REPLAY_STEP_FRAMES: usize = 30; // Pause between replay steps while playing
+pub const MS_PER_DAY: u64 = 86_400_000; // The daily dungeon changes at midnight UTC
 
 // Damage
 pub const PLAYER_HEALTH: u32 = 3;
 pub const MONSTER_HEALTH: u32 = 5;
 pub const MONSTER_DAMAGE: u32 = 1;
 pub const FIREBALL_DAMAGE: u32 = 1;
+pub const LAVA_DAMAGE: u32 = 1; // For ending a turn on lava
+pub const FOUNTAIN_HEAL: u32 = 1; // For ending a turn at a fountain
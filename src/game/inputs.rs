@@ -1,5 +1,6 @@
 use crate::GameState;
 use crate::network::send::send_reset_game;
+use crate::game::constants::MAX_SEED_DIGITS;
 use turbo::*;
 
 pub use crate::game::cards::card_input::{ handle_card_click, handle_play_area_buttons };
@@ -17,7 +18,31 @@ pub fn handle_input(state: &mut GameState) {
 pub fn handle_reset_game(state: &mut GameState) {
     let gp = gamepad::get(0);
     if gp.a.just_pressed() {
-        send_reset_game();
-        *state = GameState::new();
+        if !state.room_id.is_empty() {
+            // A typed seed deals that dungeon again, otherwise the server picks a new one
+            let seed = state.seed_input.as_ref().and_then(|seed| seed.parse().ok());
+            send_reset_game(&state.room_id, seed);
+        }
+        // The player stays in the room; the server's reset brings the new board
+        state.seed_input = None;
+        state.reset_match();
+    }
+}
+
+/// Collects digits typed during a match as the seed for the next reset
+pub fn handle_seed_input(state: &mut GameState) {
+    let keyboard = keyboard::get();
+    let mut seed = state.seed_input.take().unwrap_or_default();
+
+    for c in keyboard.chars() {
+        if c.is_ascii_digit() && seed.len() < MAX_SEED_DIGITS {
+            seed.push(c);
+        }
+    }
+    if keyboard.backspace().just_pressed() {
+        seed.pop();
+    }
+    if !seed.is_empty() && !keyboard.escape().just_pressed() {
+        state.seed_input = Some(seed);
     }
 }
@@ -1,27 +1,146 @@
-use crate::game::map::tile::{ Tile, Direction };
-use crate::game::constants::MAP_SIZE;
+use std::fmt;
+use crate::game::map::tile::{ Tile, TileKind, Direction };
+use crate::game::constants::{
+    BOARD_SIZES,
+    MIN_BOARD_SIDE,
+    MAX_BOARD_SIDE,
+    KEY_COUNT,
+    LOCKED_DOOR_COUNT,
+    LAVA_TILE_COUNT,
+    FOUNTAIN_TILE_COUNT,
+    PORTAL_PAIR_COUNT,
+    TREASURE_TILE_COUNT,
+};
 use crate::GameState;
-use turbo::random;
+use crate::rules::Rng;
+use turbo::borsh::{ BorshDeserialize, BorshSerialize };
+use serde::{ Deserialize, Serialize };
 
-pub fn random_tiles(count: usize) -> Vec<Tile> {
-    (0..count)
+/// Width and height of a match's board, in tiles. Tiles are stored row by row, so this is
+/// also what turns a tile index into a position and back.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize
+)]
+pub struct BoardSize {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Default for BoardSize {
+    fn default() -> Self {
+        let (width, height) = BOARD_SIZES[0];
+        Self { width, height }
+    }
+}
+
+impl fmt::Display for BoardSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl BoardSize {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+
+    /// Returns true if both sides are within the limits a match can be played on
+    pub fn is_valid(&self) -> bool {
+        let sides = MIN_BOARD_SIDE..=MAX_BOARD_SIDE;
+        sides.contains(&self.width) && sides.contains(&self.height)
+    }
+
+    /// The nearest size a match can be played on
+    pub fn clamped(&self) -> BoardSize {
+        BoardSize {
+            width: self.width.clamp(MIN_BOARD_SIDE, MAX_BOARD_SIDE),
+            height: self.height.clamp(MIN_BOARD_SIDE, MAX_BOARD_SIDE),
+        }
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    /// Returns true if (x, y) is on the board
+    pub fn contains(&self, position: (usize, usize)) -> bool {
+        position.0 < self.width && position.1 < self.height
+    }
+
+    /// Given a tile index, return (x, y)
+    pub fn position(&self, index: usize) -> (usize, usize) {
+        (index % self.width, index / self.width)
+    }
+
+    /// Convert (x, y) to tile index
+    pub fn index(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+
+    /// Return the index of the tile one step away in the given direction, if it is on the board
+    pub fn index_in_direction(&self, index: usize, direction: Direction) -> Option<usize> {
+        let (x, y) = self.position(index);
+        match direction {
+            Direction::Up if y > 0 => Some(self.index(x, y - 1)),
+            Direction::Down if y + 1 < self.height => Some(self.index(x, y + 1)),
+            Direction::Left if x > 0 => Some(self.index(x - 1, y)),
+            Direction::Right if x + 1 < self.width => Some(self.index(x + 1, y)),
+            _ => None,
+        }
+    }
+
+    /// The tile the monster spawns on; left or up of the middle when a side is even
+    pub fn center(&self) -> (usize, usize) {
+        ((self.width - 1) / 2, (self.height - 1) / 2)
+    }
+
+    /// The next size in the menu's list, wrapping around in either direction
+    pub fn cycled(&self, forward: bool) -> BoardSize {
+        let sizes = BOARD_SIZES.map(|(width, height)| BoardSize { width, height });
+        let current = sizes.iter().position(|size| size == self).unwrap_or(0);
+        let next = if forward {
+            (current + 1) % sizes.len()
+        } else {
+            (current + sizes.len() - 1) % sizes.len()
+        };
+        sizes[next]
+    }
+}
+
+pub fn random_tiles(size: BoardSize, rng: &mut impl Rng) -> Vec<Tile> {
+    let (center_x, center_y) = size.center();
+    let center_index = size.index(center_x, center_y);
+    let (exit_index, exit_outward) = random_exit(size, rng);
+    let mut tiles: Vec<Tile> = (0..size.tile_count())
         .map(|i| {
-            let (x, y) = Tile::position(i);
+            let (x, y) = size.position(i);
             let mut forbidden = vec![];
             if y == 0 {
                 forbidden.push(Direction::Up);
             }
-            if y == MAP_SIZE - 1 {
+            if y == size.height - 1 {
                 forbidden.push(Direction::Down);
             }
             if x == 0 {
                 forbidden.push(Direction::Left);
             }
-            if x == MAP_SIZE - 1 {
+            if x == size.width - 1 {
                 forbidden.push(Direction::Right);
             }
 
-            let tile = if i == 12 {
+            let tile = if i == exit_index {
+                let mut exit_tile = Tile::exit(exit_outward);
+                exit_tile.original_location = i;
+                exit_tile
+            } else if i == center_index {
                 // Monster spawn tile (center) - always create a 4-entrance tile
                 let mut center_tile = Tile::new(
                     vec![Direction::Up, Direction::Down, Direction::Left, Direction::Right]
@@ -29,7 +148,7 @@ pub fn random_tiles(count: usize) -> Vec<Tile> {
                 center_tile.original_location = i;
                 center_tile
             } else {
-                let mut tile = Tile::random(&forbidden);
+                let mut tile = Tile::random(&forbidden, rng);
                 // Set the original location for this tile
                 tile.original_location = i;
                 // Ensure at least one entrance remains (should be handled by Tile::random)
@@ -38,17 +157,17 @@ pub fn random_tiles(count: usize) -> Vec<Tile> {
                     if y > 0 {
                         possible.push(Direction::Up);
                     }
-                    if y < MAP_SIZE - 1 {
+                    if y < size.height - 1 {
                         possible.push(Direction::Down);
                     }
                     if x > 0 {
                         possible.push(Direction::Left);
                     }
-                    if x < MAP_SIZE - 1 {
+                    if x < size.width - 1 {
                         possible.push(Direction::Right);
                     }
                     if !possible.is_empty() {
-                        let idx = (random::u32() as usize) % possible.len();
+                        let idx = rng.pick(possible.len());
                         tile.entrances.push(possible[idx]);
                     }
                 }
@@ -57,9 +176,146 @@ pub fn random_tiles(count: usize) -> Vec<Tile> {
 
             tile
         })
+        .collect();
+
+    lock_random_doors(&mut tiles, size, rng);
+    let mut spots = free_spots(&tiles, size, center_index, rng);
+    place_special_rooms(&mut tiles, &mut spots);
+    place_random_keys(&mut tiles, &mut spots);
+    tiles
+}
+
+/// Picks an edge tile for the exit, away from the corners the players spawn in, along with
+/// the direction that faces off the board from it
+fn random_exit(size: BoardSize, rng: &mut impl Rng) -> (usize, Direction) {
+    let mut edges = vec![];
+    for x in 1..size.width - 1 {
+        edges.push((size.index(x, 0), Direction::Up));
+        edges.push((size.index(x, size.height - 1), Direction::Down));
+    }
+    for y in 1..size.height - 1 {
+        edges.push((size.index(0, y), Direction::Left));
+        edges.push((size.index(size.width - 1, y), Direction::Right));
+    }
+    edges[rng.pick(edges.len())]
+}
+
+/// Locks a few of the passages between tiles, never one leading into the exit
+fn lock_random_doors(tiles: &mut [Tile], size: BoardSize, rng: &mut impl Rng) {
+    // Only down and right, so each passage is listed once
+    let mut passages: Vec<(usize, Direction)> = (0..tiles.len())
+        .flat_map(|index| [(index, Direction::Down), (index, Direction::Right)])
+        .filter(|&(index, direction)| {
+            size.index_in_direction(index, direction).is_some_and(|neighbour| {
+                !tiles[index].is_exit &&
+                    !tiles[neighbour].is_exit &&
+                    tiles[index].is_connected_in_direction(
+                        direction,
+                        &tiles[neighbour],
+                        size.position(index),
+                        size.position(neighbour)
+                    )
+            })
+        })
+        .collect();
+    rng.shuffle(&mut passages);
+    for (index, direction) in passages.into_iter().take(LOCKED_DOOR_COUNT) {
+        tiles[index].locked.push(direction);
+    }
+}
+
+/// The tiles away from the spawn corners, the monster's tile and the exit, in random order
+fn free_spots(
+    tiles: &[Tile],
+    size: BoardSize,
+    center_index: usize,
+    rng: &mut impl Rng
+) -> Vec<usize> {
+    let corners = [
+        size.index(0, 0),
+        size.index(size.width - 1, 0),
+        size.index(0, size.height - 1),
+        size.index(size.width - 1, size.height - 1),
+    ];
+    let mut spots: Vec<usize> = (0..tiles.len())
+        .filter(|index| {
+            !corners.contains(index) && *index != center_index && !tiles[*index].is_exit
+        })
+        .collect();
+    rng.shuffle(&mut spots);
+    spots
+}
+
+/// Turns some of the free tiles into special rooms. Small boards get fewer of them, and a
+/// portal is only placed along with the other one of its pair.
+fn place_special_rooms(tiles: &mut [Tile], spots: &mut Vec<usize>) {
+    for pair in 0..PORTAL_PAIR_COUNT {
+        if spots.len() < 2 {
+            break;
+        }
+        for index in spots.drain(..2) {
+            tiles[index].kind = TileKind::Portal { pair: pair as u8 };
+        }
+    }
+
+    let rooms = [
+        (TileKind::Lava, LAVA_TILE_COUNT),
+        (TileKind::Fountain, FOUNTAIN_TILE_COUNT),
+        (TileKind::Treasure, TREASURE_TILE_COUNT),
+    ];
+    for (kind, count) in rooms {
+        let count = count.min(spots.len());
+        for index in spots.drain(..count) {
+            tiles[index].kind = kind;
+        }
+    }
+}
+
+/// Drops keys on free tiles that were not made into special rooms
+fn place_random_keys(tiles: &mut [Tile], spots: &mut Vec<usize>) {
+    let count = KEY_COUNT.min(spots.len());
+    for index in spots.drain(..count) {
+        tiles[index].has_key = true;
+    }
+}
+
+/// The other portal of the pair the tile at the given index belongs to
+pub fn paired_portal(tiles: &[Tile], tile_index: usize) -> Option<usize> {
+    match tiles.get(tile_index)?.kind {
+        TileKind::Portal { pair } => {
+            (0..tiles.len()).find(|&index| {
+                index != tile_index && tiles[index].kind == TileKind::Portal { pair }
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The passages out of a tile that a key would open: locked on either side, with an entrance
+/// on both sides so the door leads somewhere
+pub fn locked_doors_at(tiles: &[Tile], size: BoardSize, tile_index: usize) -> Vec<Direction> {
+    [Direction::Up, Direction::Down, Direction::Left, Direction::Right]
+        .into_iter()
+        .filter(|&direction| {
+            size.index_in_direction(tile_index, direction).is_some_and(|neighbour| {
+                let (tile, other) = (&tiles[tile_index], &tiles[neighbour]);
+                let back = direction.opposite();
+                tile.entrances.contains(&direction) &&
+                    other.entrances.contains(&back) &&
+                    (tile.locked.contains(&direction) || other.locked.contains(&back))
+            })
+        })
         .collect()
 }
 
+/// Opens the door on both sides of the passage out of a tile in the given direction
+pub fn unlock_door(tiles: &mut [Tile], size: BoardSize, tile_index: usize, direction: Direction) {
+    tiles[tile_index].locked.retain(|locked| *locked != direction);
+    if let Some(neighbour) = size.index_in_direction(tile_index, direction) {
+        tiles[neighbour].locked.retain(|locked| *locked != direction.opposite());
+    }
+}
+
 pub fn draw_board(state: &GameState, frame: f64, tile_size: u32, offset_x: u32, offset_y: u32) {
     // Phase 1: Draw all non-animated tiles at their logical grid positions
     for (i, tile) in state.tiles.iter().enumerate() {
@@ -70,7 +326,7 @@ pub fn draw_board(state: &GameState, frame: f64, tile_size: u32, offset_x: u32,
         }
 
         // Draw tiles at their current index positions (they haven't been swapped yet)
-        let (tx, ty) = Tile::screen_position(i, tile_size, offset_x, offset_y);
+        let (tx, ty) = Tile::screen_position(i, state.board_size, tile_size, offset_x, offset_y);
         let is_swap_selected = state.swap_tiles_selected.contains(&i);
         tile.draw(tx as i32, ty as i32, tile_size, tile.is_highlighted, frame, is_swap_selected);
     }
@@ -1,8 +1,8 @@
 use crate::game::{
     constants::{ MONSTER_HEALTH, MONSTER_DAMAGE },
-    map::{ tile::{ Direction, Tile }, Player },
+    map::{ tile::{ Direction, Tile }, BoardSize, Player, PlayerId },
 };
-use crate::server::broadcast::broadcast_player_damage_from_monster;
+use crate::rules::Rng;
 use turbo::{ borsh::{ BorshDeserialize, BorshSerialize }, * };
 use serde::{ Serialize, Deserialize };
 
@@ -21,10 +21,9 @@ pub struct Monster {
 }
 
 impl Monster {
-    pub fn new() -> Self {
-        // Spawn in center of board (MAP_SIZE = 5, so center is at (2, 2))
-        let center_x = 2;
-        let center_y = 2;
+    pub fn new(size: BoardSize) -> Self {
+        // Spawn in center of board, on the four-entrance tile random_tiles leaves there
+        let (center_x, center_y) = size.center();
 
         Self {
             position: (center_x, center_y),
@@ -40,8 +39,8 @@ impl Monster {
         }
     }
 
-    pub fn move_to(&mut self, to_index: usize) {
-        let (nx, ny) = Tile::position(to_index);
+    pub fn move_to(&mut self, to_index: usize, size: BoardSize) {
+        let (nx, ny) = size.position(to_index);
         self.position = (nx, ny);
     }
 
@@ -229,13 +228,20 @@ impl Monster {
         }
     }
 
-    pub fn take_turn(&mut self, players: &mut [Player], tiles: &[Tile]) {
+    /// Moves the monster or attacks, returns the player it dealt damage to, if any
+    pub fn take_turn(
+        &mut self,
+        players: &mut [Player],
+        tiles: &[Tile],
+        size: BoardSize,
+        rng: &mut impl Rng
+    ) -> Option<PlayerId> {
         if !self.is_alive() {
-            return;
+            return None;
         }
 
         // First, find the nearest player and get their info without borrowing mutably
-        let nearest_player_info = self.find_nearest_player_info(players, tiles);
+        let nearest_player_info = self.find_nearest_player_info(players, tiles, size);
         log!("nearest_player_info: {:?}", nearest_player_info);
 
         if let Some((player_index, player_pos)) = nearest_player_info {
@@ -248,8 +254,7 @@ impl Monster {
                 log!("Monster is on player tile, dealing damage!");
                 if let Some(player) = players.get_mut(player_index) {
                     player.take_damage(self.damage);
-                    broadcast_player_damage_from_monster(&player.id.to_string(), self.damage);
-                    return; // Don't move if we're already on the player
+                    return Some(player.id.clone()); // Don't move if we're already on the player
                 }
             }
 
@@ -261,8 +266,8 @@ impl Monster {
             if is_adjacent && (dx != 0 || dy != 0) {
                 // Skip if same position (already handled above)
                 // Only check tile connections if we're actually adjacent
-                let monster_tile_index = Tile::index(self.position.0, self.position.1);
-                let player_tile_index = Tile::index(player_pos.0, player_pos.1);
+                let monster_tile_index = size.index(self.position.0, self.position.1);
+                let player_tile_index = size.index(player_pos.0, player_pos.1);
 
                 if
                     let (Some(monster_tile), Some(player_tile)) = (
@@ -293,19 +298,15 @@ impl Monster {
                         log!("Monster is on tile connected to player tile, dealing damage!");
                         if let Some(player) = players.get_mut(player_index) {
                             player.take_damage(self.damage);
-                            broadcast_player_damage_from_monster(
-                                &player.id.to_string(),
-                                self.damage
-                            );
-                            return; // Don't move if we're connected to the player
+                            return Some(player.id.clone()); // Don't move if we're connected
                         }
                     }
                 }
             }
 
-            if let Some(direction) = self.calculate_direction_towards(player_pos, tiles) {
+            if let Some(direction) = self.calculate_direction_towards(player_pos, tiles, size) {
                 // Only move if the tiles are connected
-                if let Some(new_pos) = self.move_in_direction(direction, tiles) {
+                if let Some(new_pos) = self.move_in_direction(direction, tiles, size) {
                     // Check if new position is valid and not occupied
                     if !self.is_position_occupied(players, new_pos) {
                         let old_pos = self.position;
@@ -317,10 +318,7 @@ impl Monster {
                             log!("Monster moved onto player tile, dealing damage!");
                             if let Some(player) = players.get_mut(player_index) {
                                 player.take_damage(self.damage);
-                                broadcast_player_damage_from_monster(
-                                    &player.id.to_string(),
-                                    self.damage
-                                );
+                                return Some(player.id.clone());
                             }
                         }
                     }
@@ -331,8 +329,9 @@ impl Monster {
             self.target_player = None;
 
             // If no accessible players, move randomly to an adjacent connected tile
-            if let Some(random_direction) = self.get_random_available_direction(tiles) {
-                if let Some(new_pos) = self.move_in_direction(random_direction, tiles) {
+            let random_direction = self.get_random_available_direction(tiles, size, rng);
+            if let Some(random_direction) = random_direction {
+                if let Some(new_pos) = self.move_in_direction(random_direction, tiles, size) {
                     // Check if new position is valid and not occupied
                     if !self.is_position_occupied(players, new_pos) {
                         let old_pos = self.position;
@@ -346,6 +345,7 @@ impl Monster {
                 }
             }
         }
+        None
     }
 
     /// Get the current target player index
@@ -367,7 +367,8 @@ impl Monster {
     fn find_nearest_player_info(
         &self,
         players: &[Player],
-        tiles: &[Tile]
+        tiles: &[Tile],
+        size: BoardSize
     ) -> Option<(usize, (usize, usize))> {
         if players.is_empty() {
             return None;
@@ -378,10 +379,10 @@ impl Monster {
             if let Some(player) = players.get(current_target) {
                 if player.is_alive() {
                     // Check if there's still a valid path to our current target
-                    let start_index = Tile::index(self.position.0, self.position.1);
-                    let target_index = Tile::index(player.position.0, player.position.1);
+                    let start_index = size.index(self.position.0, self.position.1);
+                    let target_index = size.index(player.position.0, player.position.1);
 
-                    if Tile::find_walkable_path(start_index, target_index, tiles).is_some() {
+                    if Tile::find_walkable_path(start_index, target_index, tiles, size).is_some() {
                         // Keep our current target if still accessible
                         return Some((current_target, player.position));
                     }
@@ -396,10 +397,10 @@ impl Monster {
             .filter_map(|(index, player)| {
                 if player.is_alive() {
                     // Check if there's a valid path to this player
-                    let start_index = Tile::index(self.position.0, self.position.1);
-                    let target_index = Tile::index(player.position.0, player.position.1);
+                    let start_index = size.index(self.position.0, self.position.1);
+                    let target_index = size.index(player.position.0, player.position.1);
 
-                    if Tile::find_walkable_path(start_index, target_index, tiles).is_some() {
+                    if Tile::find_walkable_path(start_index, target_index, tiles, size).is_some() {
                         let dx = (player.position.0 as i32) - (self.position.0 as i32);
                         let dy = (player.position.1 as i32) - (self.position.1 as i32);
                         let distance = (dx * dx + dy * dy) as u32; // Manhattan distance squared, cast to u32
@@ -461,15 +462,16 @@ impl Monster {
     fn calculate_direction_towards(
         &self,
         target: (usize, usize),
-        tiles: &[Tile]
+        tiles: &[Tile],
+        size: BoardSize
     ) -> Option<Direction> {
-        let start_index = Tile::index(self.position.0, self.position.1);
-        let target_index = Tile::index(target.0, target.1);
+        let start_index = size.index(self.position.0, self.position.1);
+        let target_index = size.index(target.0, target.1);
 
-        if let Some(path) = Tile::find_walkable_path(start_index, target_index, tiles) {
+        if let Some(path) = Tile::find_walkable_path(start_index, target_index, tiles, size) {
             if path.len() >= 2 {
                 let next_index = path[1];
-                let (next_x, next_y) = Tile::position(next_index);
+                let (next_x, next_y) = size.position(next_index);
                 let (current_x, current_y) = self.position;
 
                 // Determine direction from current to next position
@@ -493,23 +495,28 @@ impl Monster {
     }
 
     /// Move in the given direction (only if tiles are connected)
-    fn move_in_direction(&self, direction: Direction, tiles: &[Tile]) -> Option<(usize, usize)> {
-        let current_tile_index = Tile::index(self.position.0, self.position.1);
+    fn move_in_direction(
+        &self,
+        direction: Direction,
+        tiles: &[Tile],
+        size: BoardSize
+    ) -> Option<(usize, usize)> {
+        let current_tile_index = size.index(self.position.0, self.position.1);
         let current_tile = &tiles[current_tile_index];
 
         let new_pos = match direction {
             Direction::Up => (self.position.0, self.position.1.saturating_sub(1)),
-            Direction::Down => (self.position.0, (self.position.1 + 1).min(4)),
+            Direction::Down => (self.position.0, (self.position.1 + 1).min(size.height - 1)),
             Direction::Left => (self.position.0.saturating_sub(1), self.position.1),
-            Direction::Right => ((self.position.0 + 1).min(4), self.position.1),
+            Direction::Right => ((self.position.0 + 1).min(size.width - 1), self.position.1),
         };
 
         // Check if new position is within bounds
-        if !self.is_valid_position(new_pos) {
+        if !self.is_valid_position(new_pos, size) {
             return None;
         }
 
-        let target_tile_index = Tile::index(new_pos.0, new_pos.1);
+        let target_tile_index = size.index(new_pos.0, new_pos.1);
         let target_tile = &tiles[target_tile_index];
 
         // Check if the tiles are connected in the direction we want to move
@@ -528,8 +535,8 @@ impl Monster {
     }
 
     /// Check if position is within bounds
-    fn is_valid_position(&self, pos: (usize, usize)) -> bool {
-        pos.0 < 5 && pos.1 < 5
+    fn is_valid_position(&self, pos: (usize, usize), size: BoardSize) -> bool {
+        size.contains(pos)
     }
 
     /// Check if position is occupied by a player
@@ -538,8 +545,13 @@ impl Monster {
     }
 
     /// Get a random available direction from the current position
-    fn get_random_available_direction(&self, tiles: &[Tile]) -> Option<Direction> {
-        let current_tile_index = Tile::index(self.position.0, self.position.1);
+    fn get_random_available_direction(
+        &self,
+        tiles: &[Tile],
+        size: BoardSize,
+        rng: &mut impl Rng
+    ) -> Option<Direction> {
+        let current_tile_index = size.index(self.position.0, self.position.1);
         let current_tile = &tiles[current_tile_index];
 
         let mut available_directions = Vec::new();
@@ -550,7 +562,7 @@ impl Monster {
             if
                 current_tile.is_connected_in_direction(
                     Direction::Up.into(),
-                    &tiles[Tile::index(up_pos.0, up_pos.1)],
+                    &tiles[size.index(up_pos.0, up_pos.1)],
                     self.position,
                     up_pos
                 )
@@ -559,12 +571,12 @@ impl Monster {
             }
         }
         // Check Down
-        if self.position.1 < 4 {
+        if self.position.1 + 1 < size.height {
             let down_pos = (self.position.0, self.position.1 + 1);
             if
                 current_tile.is_connected_in_direction(
                     Direction::Down.into(),
-                    &tiles[Tile::index(down_pos.0, down_pos.1)],
+                    &tiles[size.index(down_pos.0, down_pos.1)],
                     self.position,
                     down_pos
                 )
@@ -578,7 +590,7 @@ impl Monster {
             if
                 current_tile.is_connected_in_direction(
                     Direction::Left.into(),
-                    &tiles[Tile::index(left_pos.0, left_pos.1)],
+                    &tiles[size.index(left_pos.0, left_pos.1)],
                     self.position,
                     left_pos
                 )
@@ -587,12 +599,12 @@ impl Monster {
             }
         }
         // Check Right
-        if self.position.0 < 4 {
+        if self.position.0 + 1 < size.width {
             let right_pos = (self.position.0 + 1, self.position.1);
             if
                 current_tile.is_connected_in_direction(
                     Direction::Right.into(),
-                    &tiles[Tile::index(right_pos.0, right_pos.1)],
+                    &tiles[size.index(right_pos.0, right_pos.1)],
                     self.position,
                     right_pos
                 )
@@ -604,8 +616,7 @@ impl Monster {
         if available_directions.is_empty() {
             None
         } else {
-            // Use turbo's built-in random function
-            let random_index = (random::u32() as usize) % available_directions.len();
+            let random_index = rng.pick(available_directions.len());
             Some(available_directions[random_index].clone())
         }
     }
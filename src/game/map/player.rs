@@ -1,6 +1,7 @@
 use crate::game::constants::PLAYER_HEALTH;
-use crate::game::map::tile::Tile;
+use crate::game::map::board::BoardSize;
 use crate::game::cards::card::Card;
+use crate::rules::Rng;
 use turbo::{ borsh::{ BorshDeserialize, BorshSerialize }, * };
 use serde::{ Serialize, Deserialize };
 
@@ -8,6 +9,50 @@ use serde::{ Serialize, Deserialize };
 pub enum PlayerId {
     Player1,
     Player2,
+    Player3,
+    Player4,
+}
+
+impl PlayerId {
+    /// The n-th user in a room plays as the n-th id
+    pub fn from_index(index: usize) -> Option<PlayerId> {
+        match index {
+            0 => Some(PlayerId::Player1),
+            1 => Some(PlayerId::Player2),
+            2 => Some(PlayerId::Player3),
+            3 => Some(PlayerId::Player4),
+            _ => None,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        match self {
+            PlayerId::Player1 => 0,
+            PlayerId::Player2 => 1,
+            PlayerId::Player3 => 2,
+            PlayerId::Player4 => 3,
+        }
+    }
+
+    /// Each player starts in their own corner of the board
+    pub fn spawn_position(&self, size: BoardSize) -> (usize, usize) {
+        let (right, bottom) = (size.width - 1, size.height - 1);
+        match self {
+            PlayerId::Player1 => (0, 0),
+            PlayerId::Player2 => (right, bottom),
+            PlayerId::Player3 => (right, 0),
+            PlayerId::Player4 => (0, bottom),
+        }
+    }
+
+    pub fn sprite_name(&self) -> &'static str {
+        match self {
+            PlayerId::Player1 => "baby_red",
+            PlayerId::Player2 => "baby_blue",
+            PlayerId::Player3 => "baby_green",
+            PlayerId::Player4 => "baby_yellow",
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
@@ -24,7 +69,11 @@ pub struct Player {
     pub position: (usize, usize),
     pub original_position: (usize, usize),
     pub hand: Vec<Card>,
+    pub hidden_hand_count: usize, // Cards in a hand left out of what this client was sent
+    pub draw_pile_count: usize, // The piles themselves stay on the server
+    pub discard_pile_count: usize,
     pub health: u32,
+    pub keys: u32, // Picked up on the board, each one opens a locked door
     pub direction: Direction,
     pub animation_frame: usize,
     pub animation_timer: f32,
@@ -32,15 +81,17 @@ pub struct Player {
 }
 
 impl Player {
-    pub fn new(id: PlayerId, x: usize, y: usize, hand_size: usize, is_dummy: bool) -> Self {
-        let hand = Self::new_hand(hand_size, is_dummy);
-
+    pub fn new(id: PlayerId, x: usize, y: usize, hand: Vec<Card>) -> Self {
         Self {
             id,
             position: (x, y),
             original_position: (x, y),
             hand,
+            hidden_hand_count: 0,
+            draw_pile_count: 0,
+            discard_pile_count: 0,
             health: PLAYER_HEALTH,
+            keys: 0,
             direction: Direction::Down, // Default direction
             animation_frame: 0,
             animation_timer: 0.0,
@@ -49,16 +100,16 @@ impl Player {
     }
 
     // No duplicates in hand
-    pub fn new_hand(hand_size: usize, is_dummy: bool) -> Vec<Card> {
+    pub fn new_hand(hand_size: usize, is_dummy: bool, rng: &mut impl Rng) -> Vec<Card> {
         if is_dummy {
             (0..hand_size).map(|_| Card::dummy_card()).collect()
         } else {
-            Card::get_unique_cards().into_iter().take(hand_size).collect()
+            Card::get_unique_cards(rng).into_iter().take(hand_size).collect()
         }
     }
 
-    pub fn move_to(&mut self, to_index: usize) {
-        let (nx, ny) = Tile::position(to_index);
+    pub fn move_to(&mut self, to_index: usize, size: BoardSize) {
+        let (nx, ny) = size.position(to_index);
         self.position = (nx, ny);
     }
 
@@ -70,6 +121,18 @@ impl Player {
         self.original_position = self.position;
     }
 
+    /// A copy safe to send to other players: the hand is replaced by its card count
+    pub fn without_hand(&self) -> Player {
+        Player {
+            hand: Vec::new(),
+            hidden_hand_count: self.hand
+                .iter()
+                .filter(|card| !card.is_dummy())
+                .count(),
+            ..self.clone()
+        }
+    }
+
     // Health management methods
     pub fn take_damage(&mut self, amount: u32) {
         if amount >= self.health {
@@ -80,7 +143,7 @@ impl Player {
     }
 
     pub fn heal(&mut self, amount: u32) {
-        self.health = self.health.saturating_add(amount);
+        self.health = self.health.saturating_add(amount).min(PLAYER_HEALTH);
     }
 
     pub fn is_alive(&self) -> bool {
@@ -162,10 +225,7 @@ impl Player {
         let sprite_x = self.animation_frame; // Use animation frame to cycle through frames
 
         // Select sprite based on player ID
-        let sprite_name = match self.id {
-            PlayerId::Player1 => "baby_red",
-            PlayerId::Player2 => "baby_blue",
-        };
+        let sprite_name = self.id.sprite_name();
 
         // Calculate texture coordinates for the 4x4 spritesheet
         let frame_width = 36;
@@ -1,18 +1,25 @@
 use crate::game::constants::{
     DEBUG_MODE,
     FLASH_SPEED,
-    MAP_SIZE,
+    LOCKED_DOOR_COLOR,
+    PORTAL_PAIR_COLORS,
     ENTRANCE_COUNT_WEIGHT_1,
     ENTRANCE_COUNT_WEIGHT_2,
     ENTRANCE_COUNT_WEIGHT_3,
     ENTRANCE_COUNT_WEIGHT_4,
 };
+use crate::game::map::board::BoardSize;
+use crate::rules::Rng;
 use turbo::{ borsh::{ BorshDeserialize, BorshSerialize }, * };
 use serde::{ Deserialize, Serialize };
 
 #[derive(Clone, Debug, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 pub struct Tile {
     pub entrances: Vec<Direction>,
+    pub kind: TileKind, // What the room does to the players in it, it goes wherever the tile goes
+    pub locked: Vec<Direction>, // Entrances behind a locked door, closed until a key opens them
+    pub is_exit: bool, // The babies escape once all of them stand on this tile
+    pub has_key: bool, // Taken by the next player to confirm a move onto the tile
     pub is_highlighted: bool,
     #[serde(skip, default)]
     pub rotation_anim: Option<TileRotationAnim>,
@@ -22,6 +29,43 @@ pub struct Tile {
     pub target_rotation: u8, // 0=0deg, 1=90deg, 2=180deg, 3=270deg
 }
 
+/// The kind of room a tile is. Most are plain corridors; the rest act on the players in them.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize
+)]
+pub enum TileKind {
+    #[default]
+    Corridor,
+    Lava, // Burns a player ending their turn on it
+    Fountain, // Heals a player ending their turn at it
+    Portal {
+        pair: u8, // Entering one portal comes out at the other one of the same pair
+    },
+    Treasure, // Draws a card from the deck of the first player whose move into it is kept
+}
+
+impl TileKind {
+    /// The sprite drawn over the tile, if the room has one
+    pub fn overlay_sprite(&self) -> Option<&'static str> {
+        match self {
+            TileKind::Corridor => None,
+            TileKind::Lava => Some("lava"),
+            TileKind::Fountain => Some("fountain"),
+            TileKind::Portal { .. } => Some("portal"),
+            TileKind::Treasure => Some("treasure"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 pub struct TileRotationAnim {
     pub from_angle: f32,
@@ -49,10 +93,35 @@ pub enum Direction {
     Right,
 }
 
+impl Direction {
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    /// The direction after turning clockwise by the given number of quarter turns
+    pub fn rotated(&self, quarter_turns: usize) -> Direction {
+        let clockwise = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+        let current = clockwise
+            .iter()
+            .position(|direction| direction == self)
+            .unwrap_or(0);
+        clockwise[(current + quarter_turns) % 4]
+    }
+}
+
 impl Tile {
     pub fn new(entrances: Vec<Direction>) -> Self {
         Self {
             entrances,
+            kind: TileKind::Corridor,
+            locked: Vec::new(),
+            is_exit: false,
+            has_key: false,
             is_highlighted: false,
             rotation_anim: None,
             original_rotation: 0,
@@ -62,7 +131,16 @@ impl Tile {
         }
     }
 
-    pub fn random(forbidden: &[Direction]) -> Self {
+    /// The way out of the dungeon. Its only entrance faces off the board, so it is closed
+    /// until the tile is rotated or swapped to open it up.
+    pub fn exit(outward: Direction) -> Self {
+        Self {
+            is_exit: true,
+            ..Tile::new(vec![outward])
+        }
+    }
+
+    pub fn random(forbidden: &[Direction], rng: &mut impl Rng) -> Self {
         use crate::game::map::tile::Direction::*;
         let all_directions = [Up, Down, Left, Right];
         let allowed: Vec<Direction> = all_directions
@@ -78,45 +156,26 @@ impl Tile {
             ENTRANCE_COUNT_WEIGHT_4,
         ];
         let weights = &all_weights[..max_count];
-        let entrance_count = random_weighted_entrance_count_dynamic(weights) as usize;
+        let entrance_count = random_weighted_entrance_count_dynamic(weights, rng) as usize;
         let mut dirs = allowed.clone();
-        random::shuffle(&mut dirs);
+        rng.shuffle(&mut dirs);
         let mut entrances = dirs.into_iter().take(entrance_count).collect::<Vec<_>>();
         // Ensure at least one entrance
         if entrances.is_empty() && !allowed.is_empty() {
-            entrances.push(allowed[(random::u32() as usize) % allowed.len()]);
+            entrances.push(allowed[rng.pick(allowed.len())]);
         }
         Tile::new(entrances)
     }
 
-    /// Given a tile index, return (x, y)
-    pub fn position(index: usize) -> (usize, usize) {
-        (index % MAP_SIZE, index / MAP_SIZE)
-    }
-
-    /// Return x from index
-    pub fn x(index: usize) -> usize {
-        index % MAP_SIZE
-    }
-
-    /// Return y from index
-    pub fn y(index: usize) -> usize {
-        index / MAP_SIZE
-    }
-
-    /// Convert (x, y) to tile index
-    pub fn index(x: usize, y: usize) -> usize {
-        y * MAP_SIZE + x
-    }
-
     /// Given a tile index and tile_size, return (tx, ty) screen coordinates
     pub fn screen_position(
         index: usize,
+        size: BoardSize,
         tile_size: u32,
         offset_x: u32,
         offset_y: u32
     ) -> (u32, u32) {
-        let (x, y) = Self::position(index);
+        let (x, y) = size.position(index);
         let tx = offset_x + (x as u32) * tile_size;
         let ty = offset_y + (y as u32) * tile_size;
         (tx, ty)
@@ -125,14 +184,15 @@ impl Tile {
     /// Returns all valid indices adjacent to (px, py)
     pub fn get_adjacent_indices(
         origin_index: usize,
+        size: BoardSize,
         include_diagonals: bool,
         include_self: bool
     ) -> Vec<usize> {
-        let (px, py) = Tile::position(origin_index);
+        let (px, py) = size.position(origin_index);
         let mut indices = vec![];
 
-        for y in 0..MAP_SIZE {
-            for x in 0..MAP_SIZE {
+        for y in 0..size.height {
+            for x in 0..size.width {
                 let dx = ((x as isize) - (px as isize)).abs();
                 let dy = ((y as isize) - (py as isize)).abs();
 
@@ -148,7 +208,7 @@ impl Tile {
                     (include_diagonals && (is_adjacent || is_diagonal || is_self)) ||
                     (!include_diagonals && is_adjacent)
                 {
-                    indices.push(Tile::index(x, y));
+                    indices.push(size.index(x, y));
                 }
             }
         }
@@ -157,7 +217,12 @@ impl Tile {
     }
 
     /// Find all reachable tiles from the current position through connected entrances
-    pub fn find_reachable_tiles(&self, start_index: usize, tiles: &[Tile]) -> Vec<usize> {
+    pub fn find_reachable_tiles(
+        &self,
+        start_index: usize,
+        tiles: &[Tile],
+        size: BoardSize
+    ) -> Vec<usize> {
         let mut visited = std::collections::HashSet::new();
         let mut reachable = Vec::new();
         let mut to_visit = vec![start_index];
@@ -171,7 +236,7 @@ impl Tile {
             }
 
             let current_tile = &tiles[current_index];
-            let (cx, cy) = Tile::position(current_index);
+            let (cx, cy) = size.position(current_index);
 
             // Check all four directions
             let directions = [
@@ -186,11 +251,11 @@ impl Tile {
                 let ny = ((cy as isize) + dy) as usize;
 
                 // Check bounds
-                if nx >= MAP_SIZE || ny >= MAP_SIZE {
+                if !size.contains((nx, ny)) {
                     continue;
                 }
 
-                let next_index = Tile::index(nx, ny);
+                let next_index = size.index(nx, ny);
 
                 // Skip if already visited
                 if visited.contains(&next_index) {
@@ -200,10 +265,7 @@ impl Tile {
                 let next_tile = &tiles[next_index];
 
                 // Check if entrances connect
-                if
-                    current_tile.entrances.contains(&from_dir) &&
-                    next_tile.entrances.contains(&to_dir)
-                {
+                if current_tile.is_open(from_dir) && next_tile.is_open(to_dir) {
                     visited.insert(next_index);
                     to_visit.push(next_index);
                 }
@@ -217,7 +279,8 @@ impl Tile {
     pub fn find_walkable_path(
         start_index: usize,
         target_index: usize,
-        tiles: &[Tile]
+        tiles: &[Tile],
+        size: BoardSize
     ) -> Option<Vec<usize>> {
         if start_index == target_index {
             return Some(vec![start_index]);
@@ -245,7 +308,7 @@ impl Tile {
             }
 
             let current_tile = &tiles[current_index];
-            let (cx, cy) = Tile::position(current_index);
+            let (cx, cy) = size.position(current_index);
 
             // Check all four directions
             let directions = [
@@ -260,11 +323,11 @@ impl Tile {
                 let ny = ((cy as isize) + dy) as usize;
 
                 // Check bounds
-                if nx >= MAP_SIZE || ny >= MAP_SIZE {
+                if !size.contains((nx, ny)) {
                     continue;
                 }
 
-                let next_index = Tile::index(nx, ny);
+                let next_index = size.index(nx, ny);
 
                 // Skip if already visited
                 if visited.contains(&next_index) {
@@ -274,10 +337,7 @@ impl Tile {
                 let next_tile = &tiles[next_index];
 
                 // Check if entrances connect
-                if
-                    current_tile.entrances.contains(&from_dir) &&
-                    next_tile.entrances.contains(&to_dir)
-                {
+                if current_tile.is_open(from_dir) && next_tile.is_open(to_dir) {
                     visited.insert(next_index);
                     parent.insert(next_index, current_index);
                     queue.push_back(next_index);
@@ -288,6 +348,11 @@ impl Tile {
         None // No path found
     }
 
+    /// Returns true if the tile has an entrance in this direction that is not locked
+    pub fn is_open(&self, direction: Direction) -> bool {
+        self.entrances.contains(&direction) && !self.locked.contains(&direction)
+    }
+
     /// Check if this tile is connected to an adjacent tile in the given direction
     /// Returns true if both tiles are adjacent AND have entrances that connect to each other
     pub fn is_connected_in_direction(
@@ -313,25 +378,8 @@ impl Tile {
             return false;
         }
 
-        // Now check if the entrances connect
-        match direction {
-            Direction::Up => {
-                self.entrances.contains(&Direction::Up) &&
-                    adjacent_tile.entrances.contains(&Direction::Down)
-            }
-            Direction::Down => {
-                self.entrances.contains(&Direction::Down) &&
-                    adjacent_tile.entrances.contains(&Direction::Up)
-            }
-            Direction::Left => {
-                self.entrances.contains(&Direction::Left) &&
-                    adjacent_tile.entrances.contains(&Direction::Right)
-            }
-            Direction::Right => {
-                self.entrances.contains(&Direction::Right) &&
-                    adjacent_tile.entrances.contains(&Direction::Left)
-            }
-        }
+        // Now check if the entrances connect; a locked door on either side keeps them apart
+        self.is_open(direction) && adjacent_tile.is_open(direction.opposite())
     }
 
     /// Finds all tiles in a straight line that have connected entrances
@@ -340,68 +388,28 @@ impl Tile {
         start_index: usize,
         direction: Direction,
         tiles: &[Tile],
+        size: BoardSize,
         max_distance: Option<usize>
     ) -> Vec<usize> {
         let mut connected_tiles = vec![start_index];
         let mut current_index = start_index;
-        let max_dist = max_distance.unwrap_or(MAP_SIZE);
+        let max_dist = max_distance.unwrap_or(size.width.max(size.height));
 
         for _ in 0..max_dist {
-            let next_index = match direction {
-                Direction::Up => {
-                    if current_index < MAP_SIZE {
-                        break;
-                    }
-                    current_index - MAP_SIZE
-                }
-                Direction::Down => {
-                    let new_index = current_index + MAP_SIZE;
-                    if new_index >= tiles.len() {
-                        break;
-                    }
-                    new_index
-                }
-                Direction::Left => {
-                    if current_index % MAP_SIZE == 0 {
-                        break;
-                    }
-                    current_index - 1
-                }
-                Direction::Right => {
-                    if (current_index + 1) % MAP_SIZE == 0 {
-                        break;
-                    }
-                    current_index + 1
+            // Check if we've reached the edge or if the tiles are connected
+            let next_index = match size.index_in_direction(current_index, direction) {
+                Some(index) if index < tiles.len() => index,
+                _ => {
+                    break;
                 }
             };
 
-            // Check if we've reached the edge or if the tiles are connected
-            if next_index == current_index || next_index >= tiles.len() {
-                break;
-            }
-
             let current_tile = &tiles[current_index];
             let next_tile = &tiles[next_index];
 
             // Check if the tiles have matching entrances
-            let has_connection = match direction {
-                Direction::Up => {
-                    current_tile.entrances.contains(&Direction::Up) &&
-                        next_tile.entrances.contains(&Direction::Down)
-                }
-                Direction::Down => {
-                    current_tile.entrances.contains(&Direction::Down) &&
-                        next_tile.entrances.contains(&Direction::Up)
-                }
-                Direction::Left => {
-                    current_tile.entrances.contains(&Direction::Left) &&
-                        next_tile.entrances.contains(&Direction::Right)
-                }
-                Direction::Right => {
-                    current_tile.entrances.contains(&Direction::Right) &&
-                        next_tile.entrances.contains(&Direction::Left)
-                }
-            };
+            let has_connection =
+                current_tile.is_open(direction) && next_tile.is_open(direction.opposite());
 
             if has_connection {
                 connected_tiles.push(next_index);
@@ -423,29 +431,12 @@ impl Tile {
     // Rotate the entrances to a specific rotation
     pub fn rotate_entrances(&mut self, rotation: u8) {
         // Calculate how many 90-degree rotations we need to apply from the current state
-        let rotations_needed = (4 + (rotation as i32) - (self.current_rotation as i32)) % 4;
+        let rotations_needed = (4 + (rotation as usize) - (self.current_rotation as usize)) % 4;
 
-        self.entrances = self.entrances
-            .iter()
-            .map(|dir| {
-                let mut n = match dir {
-                    Direction::Up => 0,
-                    Direction::Right => 1,
-                    Direction::Down => 2,
-                    Direction::Left => 3,
-                };
-                // Apply the calculated rotations
-                n = (n + (rotations_needed as usize)) % 4;
-                let result = match n {
-                    0 => Direction::Up,
-                    1 => Direction::Right,
-                    2 => Direction::Down,
-                    3 => Direction::Left,
-                    _ => unreachable!(),
-                };
-                result
-            })
-            .collect();
+        // Locked doors turn along with the entrances they sit in
+        for dir in self.entrances.iter_mut().chain(self.locked.iter_mut()) {
+            *dir = dir.rotated(rotations_needed);
+        }
 
         self.current_rotation = rotation;
     }
@@ -459,7 +450,13 @@ impl Tile {
         frame: f64,
         is_swap_selected: bool
     ) {
-        let (sprite_name, rotation_offset) = self.get_wall_sprite_and_rotation();
+        let (wall_sprite, rotation_offset) = self.get_wall_sprite_and_rotation();
+        // The exit has its own set of sprites, one for each wall sprite
+        let sprite_name = if self.is_exit {
+            wall_sprite.replacen("wall_", "exit_", 1)
+        } else {
+            wall_sprite.to_string()
+        };
 
         // Calculate the final rotation including any animation
         let final_rotation = if let Some(anim) = &self.rotation_anim {
@@ -468,7 +465,31 @@ impl Tile {
             rotation_offset
         };
 
-        sprite!(sprite_name, x = x, y = y, w = tile_size, h = tile_size, rotation = final_rotation);
+        sprite!(
+            &sprite_name,
+            x = x,
+            y = y,
+            w = tile_size,
+            h = tile_size,
+            rotation = final_rotation
+        );
+
+        self.draw_room_overlay(x, y, tile_size);
+
+        // Doors and keys are left out while the tile turns, they snap back in once it settles
+        if self.rotation_anim.is_none() {
+            self.draw_locked_doors(x, y, tile_size);
+            if self.has_key {
+                let key_size = (tile_size as i32) / 3;
+                sprite!(
+                    "key",
+                    x = x + ((tile_size as i32) - key_size) / 2,
+                    y = y + ((tile_size as i32) - key_size) / 2,
+                    w = key_size,
+                    h = key_size
+                );
+            }
+        }
 
         if DEBUG_MODE {
             // Draw entrance text (URDL) on the tile
@@ -529,6 +550,57 @@ impl Tile {
         }
     }
 
+    /// Draws the sprite of a special room over the middle of the tile. It does not turn with
+    /// the walls, so it is drawn the same way during a rotation.
+    fn draw_room_overlay(&self, x: i32, y: i32, tile_size: u32) {
+        let overlay = match self.kind.overlay_sprite() {
+            Some(overlay) => overlay,
+            None => {
+                return;
+            }
+        };
+        // Portals of a pair share a tint, so the pairs can be told apart
+        let color = match self.kind {
+            TileKind::Portal { pair } => {
+                PORTAL_PAIR_COLORS[(pair as usize) % PORTAL_PAIR_COLORS.len()]
+            }
+            _ => 0xffffffff,
+        };
+        let overlay_size = (tile_size as i32) / 2;
+        let inset = ((tile_size as i32) - overlay_size) / 2;
+        sprite!(
+            overlay,
+            x = x + inset,
+            y = y + inset,
+            w = overlay_size,
+            h = overlay_size,
+            color = color
+        );
+    }
+
+    /// Draws a door across each locked entrance, at the tile's edge
+    fn draw_locked_doors(&self, x: i32, y: i32, tile_size: u32) {
+        let size = tile_size as i32;
+        let (length, depth) = (size / 3, size / 8);
+        let far_edge = size - depth;
+        for direction in &self.locked {
+            let (door_x, door_y, w, h) = match direction {
+                Direction::Up => (x + length, y, length, depth),
+                Direction::Down => (x + length, y + far_edge, length, depth),
+                Direction::Left => (x, y + length, depth, length),
+                Direction::Right => (x + far_edge, y + length, depth, length),
+            };
+            rect!(
+                x = door_x,
+                y = door_y,
+                w = w,
+                h = h,
+                color = LOCKED_DOOR_COLOR,
+                border_radius = 2
+            );
+        }
+    }
+
     /// Returns a string representation of the tile's entrances (URDL format)
     fn get_entrance_text(&self, rotation_offset: f32) -> String {
         let mut text = String::new();
@@ -629,48 +701,30 @@ impl Tile {
         &self,
         current_index: usize,
         direction: Direction,
-        tiles: &[Tile]
+        tiles: &[Tile],
+        size: BoardSize
     ) -> bool {
-        let (tile_x, tile_y) = Tile::position(current_index);
-
-        // Check if we're at the map edge
-        let at_edge = match direction {
-            Direction::Up => tile_y == 0,
-            Direction::Down => tile_y == 4,
-            Direction::Left => tile_x == 0,
-            Direction::Right => tile_x == 4,
-        };
-
-        if at_edge {
-            return true; // Hit map boundary
-        }
-
-        // Calculate next tile index
-        let next_tile_index = match direction {
-            Direction::Up => current_index - 5,
-            Direction::Down => current_index + 5,
-            Direction::Left => current_index - 1,
-            Direction::Right => current_index + 1,
+        // Calculate next tile index, there is none past the map edge
+        let next_tile_index = match size.index_in_direction(current_index, direction) {
+            Some(index) if index < tiles.len() => index,
+            _ => {
+                return true; // Hit map boundary
+            }
         };
 
-        // Check if next tile exists and is connected
-        if next_tile_index >= tiles.len() {
-            return true; // Out of bounds
-        }
-
         let next_tile = &tiles[next_tile_index];
         !self.is_connected_in_direction(
             direction,
             next_tile,
-            (tile_x, tile_y),
-            Tile::position(next_tile_index)
+            size.position(current_index),
+            size.position(next_tile_index)
         )
     }
 
     /// Check if a fireball has reached the far edge of a tile when moving in a given direction
     /// This is used to determine when to check for wall collisions
     pub fn has_fireball_reached_far_edge(
-        current_index: usize,
+        tile_position: (usize, usize),
         direction: Direction,
         new_pos: (f32, f32),
         fireball_radius: f32,
@@ -678,7 +732,7 @@ impl Tile {
         offset_x: u32,
         offset_y: u32
     ) -> bool {
-        let (tile_x, tile_y) = Tile::position(current_index);
+        let (tile_x, tile_y) = tile_position;
 
         match direction {
             Direction::Up => {
@@ -701,49 +755,11 @@ impl Tile {
             }
         }
     }
-
-    /// Check if a fireball at the given screen position would hit a player
-    /// This checks if the fireball's circular area overlaps with any player's position
-    pub fn would_fireball_hit_player(
-        fireball_pos: (f32, f32),
-        fireball_radius: f32,
-        player_positions: &[(usize, usize)],
-        tile_size: u32,
-        offset_x: u32,
-        offset_y: u32
-    ) -> Option<usize> {
-        for (player_index, &player_pos) in player_positions.iter().enumerate() {
-            // Convert player tile position to screen position (center of tile)
-            let player_screen_x =
-                (offset_x as f32) +
-                (player_pos.0 as f32) * (tile_size as f32) +
-                (tile_size as f32) / 2.0;
-            let player_screen_y =
-                (offset_y as f32) +
-                (player_pos.1 as f32) * (tile_size as f32) +
-                (tile_size as f32) / 2.0;
-
-            // Calculate distance between fireball center and player center
-            let dx = fireball_pos.0 - player_screen_x;
-            let dy = fireball_pos.1 - player_screen_y;
-            let distance = (dx * dx + dy * dy).sqrt();
-
-            // Check if fireball radius overlaps with player (assuming player takes up most of tile)
-            let player_radius = (tile_size as f32) / 3.0; // Player is roughly 2/3 of tile size
-            let collision_distance = fireball_radius + player_radius;
-
-            if distance <= collision_distance {
-                return Some(player_index);
-            }
-        }
-
-        None // No player hit
-    }
 }
 
-fn random_weighted_entrance_count_dynamic(weights: &[f32]) -> u8 {
+fn random_weighted_entrance_count_dynamic(weights: &[f32], rng: &mut impl Rng) -> u8 {
     let total: f32 = weights.iter().sum();
-    let mut pick = random::f32() * total;
+    let mut pick = rng.next_f32() * total;
     for (i, &weight) in weights.iter().enumerate() {
         if pick < weight {
             return (i + 1) as u8;
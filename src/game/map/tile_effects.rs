@@ -1,13 +1,14 @@
 use crate::game::cards::card_effect::CardEffect;
 use crate::game::map::tile::{ Tile, Direction };
-use crate::game::constants::MAP_SIZE;
+use crate::game::map::board::BoardSize;
 
 pub fn highlight_tiles_for_effect(
     effect: &CardEffect,
     player_pos: (usize, usize),
-    tiles: &mut [Tile]
+    tiles: &mut [Tile],
+    size: BoardSize
 ) {
-    let current_index = player_pos.1 * MAP_SIZE + player_pos.0;
+    let current_index = size.index(player_pos.0, player_pos.1);
     let current_tile = tiles[current_index].clone();
 
     match effect {
@@ -15,7 +16,7 @@ pub fn highlight_tiles_for_effect(
 
         CardEffect::MoveOneTile => {
             // Find all reachable tiles through connected entrances
-            let reachable_indices = current_tile.find_reachable_tiles(current_index, tiles);
+            let reachable_indices = current_tile.find_reachable_tiles(current_index, tiles, size);
             for &index in &reachable_indices {
                 tiles[index].is_highlighted = true;
             }
@@ -26,7 +27,7 @@ pub fn highlight_tiles_for_effect(
             for tile in tiles.iter_mut() {
                 tile.original_rotation = tile.current_rotation;
             }
-            for i in Tile::get_adjacent_indices(current_index, true, true) {
+            for i in Tile::get_adjacent_indices(current_index, size, true, true) {
                 tiles[i].is_highlighted = true;
             }
         }
@@ -34,7 +35,7 @@ pub fn highlight_tiles_for_effect(
         CardEffect::SwapCard => {
             // Highlight current tile and all adjacent + diagonal tiles (9 total)
             tiles[current_index].is_highlighted = true; // Current tile
-            for i in Tile::get_adjacent_indices(current_index, true, true) {
+            for i in Tile::get_adjacent_indices(current_index, size, true, true) {
                 tiles[i].is_highlighted = true;
             }
         }
@@ -49,6 +50,7 @@ pub fn highlight_tiles_for_effect(
                     current_index,
                     direction,
                     tiles,
+                    size,
                     None
                 );
                 for &index in &connected_indices {
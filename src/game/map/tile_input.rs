@@ -15,7 +15,7 @@ pub fn handle_tile_selection(
     let (_canvas_width, _canvas_height, tile_size, offset_x, offset_y) =
         state.get_board_layout(false);
     for (i, _tile) in state.tiles.iter().enumerate() {
-        let (tx, ty) = Tile::screen_position(i, tile_size, offset_x, offset_y);
+        let (tx, ty) = Tile::screen_position(i, state.board_size, tile_size, offset_x, offset_y);
         let bounds = Bounds::new(tx, ty, tile_size, tile_size);
         let mx = pointer_xy.0;
         let my = pointer_xy.1;
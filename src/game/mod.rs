@@ -6,3 +6,4 @@ pub mod util;
 pub mod ui;
 pub mod animation;
 pub mod debug;
+pub mod replay;
@@ -0,0 +1,83 @@
+use serde::{ Serialize, Deserialize };
+use borsh::{ BorshSerialize, BorshDeserialize };
+use crate::rules::{ Match, MatchEvent, Replay, ReplayStep, SeededRng };
+use crate::protocol::{ GameEvent, BoardSnapshot };
+use crate::network::event_queue::EventQueue;
+use crate::scene::Scene;
+
+/// Plays a recorded match back one step at a time. Each step is played on a local copy of
+/// the match, and what happened goes through the same event queue and animations as a live
+/// match.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    pub game: Match, // The match as of the steps played so far
+    pub rng: SeededRng,
+    pub next_step: usize,
+    pub last_seq: u64, // Sequence number of the last update handed to the event queue
+    pub playing: bool,
+    pub next_step_frame: usize, // While playing, the next step waits for this frame
+    pub exit_scene: Scene, // The game over screen the replay was started from
+}
+
+impl ReplayPlayer {
+    /// Sets the recorded match up and queues its first turn
+    pub fn new(replay: Replay, exit_scene: Scene, events: &mut EventQueue) -> Self {
+        let mut player = Self {
+            game: replay.start.clone(),
+            rng: replay.rng.clone(),
+            replay,
+            next_step: 0,
+            last_seq: 0,
+            playing: true,
+            next_step_frame: 0,
+            exit_scene,
+        };
+        // The recording starts right before the first turn
+        let first_turn = player.game.begin_turn();
+        player.queue(first_turn, events);
+        player
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_step >= self.replay.steps.len()
+    }
+
+    /// Plays the next recorded step and queues what came of it
+    pub fn step(&mut self, events: &mut EventQueue) {
+        let step = match self.replay.steps.get(self.next_step) {
+            Some(step) => step.clone(),
+            None => {
+                return;
+            }
+        };
+        self.next_step += 1;
+
+        let match_events = match step {
+            // Only accepted actions were recorded, so they are accepted again
+            ReplayStep::Action { player_index, action } => {
+                self.game.play(player_index, &action, &mut self.rng).unwrap_or_default()
+            }
+            ReplayStep::Paused => {
+                let mut match_events = self.game.pause();
+                match_events.extend(self.game.begin_turn());
+                match_events
+            }
+        };
+        self.queue(match_events, events);
+    }
+
+    /// Numbers the events like the server would; a new turn brings the whole board along
+    fn queue(&mut self, match_events: Vec<MatchEvent>, events: &mut EventQueue) {
+        let players = &self.replay.players;
+        for event in match_events {
+            self.last_seq += 1;
+            if let MatchEvent::TurnStarted { .. } = event {
+                let board = BoardSnapshot::of_match(&self.game, self.replay.seed(), players);
+                events.push_snapshot(self.last_seq, board);
+            } else if let Some(game_event) = GameEvent::from_match_event(event, players) {
+                events.push_event(self.last_seq, game_event);
+            }
+        }
+    }
+}
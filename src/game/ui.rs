@@ -1,9 +1,26 @@
 use turbo::*;
 
-use crate::game::constants::{ GAME_PADDING, FONT_HEIGHT, POSITIVE_BG_COLOR, NEGATIVE_BG_COLOR };
-use crate::network::send::send_end_turn;
+use crate::game::constants::{
+    GAME_PADDING,
+    FONT_HEIGHT,
+    POSITIVE_BG_COLOR,
+    NEGATIVE_BG_COLOR,
+};
+use crate::network::send::{ send_end_turn, send_take_seat, send_set_ready };
+use crate::protocol::{ RoomInfo, JoinRejectReason, IncompatibleReason, DailyResult };
+use crate::game::util::date_from_day;
+use crate::scene::GameMode;
+use crate::rules::MatchOutcome;
+use crate::game::replay::ReplayPlayer;
 
 const BUTTON_WIDTH: u32 = 100;
+const MAX_LISTED_ROOMS: usize = 2;
+const MENU_ROWS_Y: u32 = 485; // Below the credits box in draw_menu
+const DAILY_ROW_Y: u32 = 8; // Above the title in draw_menu
+const DAILY_RESULTS_Y: u32 = 420;
+const MAX_DAILY_RESULTS: usize = 5;
+const LOBBY_TITLE_Y: u32 = 48;
+const LOBBY_SEATS_Y: u32 = 120;
 
 /// Helper function to draw a text box with outline, fill, and text
 pub fn draw_text_box(
@@ -46,7 +63,7 @@ pub fn draw_text_box(
 }
 
 // Draw a button to end the turn
-pub fn draw_end_turn_button() {
+pub fn draw_end_turn_button(room_id: &str) {
     let font_height = FONT_HEIGHT;
     let button_height = font_height + GAME_PADDING;
 
@@ -77,7 +94,7 @@ pub fn draw_end_turn_button() {
 
     // if the button is pressed, send end turn
     if button_bounds.contains(&pointer_bounds) && pointer.just_pressed() {
-        send_end_turn();
+        send_end_turn(room_id);
     }
 }
 
@@ -102,7 +119,18 @@ pub fn draw_text(text: &str, is_my_turn: bool) {
     draw_text_box(rect_x as f32, rect_y as f32, rect_w, rect_h, text, 0xffffffff, 0x222222ff);
 }
 
-pub fn draw_menu(game_over: bool, frame: usize) {
+/// Draws the match's seed in the corner, or the seed being typed for the next reset
+pub fn draw_seed_label(seed: u64, seed_input: Option<&str>) {
+    let label = match seed_input {
+        Some(seed_input) => format!("Next seed: {}_", seed_input),
+        None => format!("Seed: {}", seed),
+    };
+    text!(&label, x = 4, y = 4, font = "small", color = 0xffffffaa);
+}
+
+/// Draws the title screen. When `room_code` is set the credits make way for the code of the
+/// private room this client is hosting.
+pub fn draw_menu(game_over: bool, frame: usize, room_code: Option<&str>, hint: &str) {
     // Draw the title sprite at the top center of the screen with bobbing animation
     let canvas_bounds = bounds::screen();
     let canvas_width = canvas_bounds.w();
@@ -118,7 +146,22 @@ pub fn draw_menu(game_over: bool, frame: usize) {
 
     sprite!("title", x = title_x as i32, y = title_y as i32, w = title_width, h = title_height);
 
-    if !game_over {
+    if let Some(code) = room_code {
+        let text = format!("Room code: {}\n\nShare it with a friend", code);
+        let text_width = 240;
+        let text_height = 45;
+        let text_x = (canvas_width - text_width) / 2;
+        let text_y = base_y + (title_height as f32) + 20.0;
+        draw_text_box(
+            text_x as f32,
+            text_y,
+            text_width,
+            text_height,
+            &text,
+            0xffffffff,
+            POSITIVE_BG_COLOR
+        );
+    } else if !game_over {
         let text = "Created by Pegasus Games\n\nunicornwithwings.com";
         let text_width = 220;
         let text_height = 45;
@@ -127,44 +170,454 @@ pub fn draw_menu(game_over: bool, frame: usize) {
         draw_text_box(text_x as f32, text_y, text_width, text_height, text, 0xffffffff, 0x222222ff);
     }
 
-    let menu_items = if game_over {
-        ["Press SPACE to return to menu"]
+    draw_text(hint, false);
+}
+
+/// Draws a clickable text box and returns true when it was clicked this frame
+fn draw_button(x: u32, y: u32, width: u32, height: u32, label: &str, fill_color: u32) -> bool {
+    let pointer = mouse::screen();
+    let button_bounds = turbo::Bounds::new(x, y, width, height);
+    let pointer_bounds = turbo::Bounds::new(pointer.x as u32, pointer.y as u32, 1, 1);
+    let hovered = button_bounds.contains(&pointer_bounds);
+    let fill_color = if hovered { 0x222222ff } else { fill_color };
+
+    draw_text_box(x as f32, y as f32, width, height, label, 0xffffffff, fill_color);
+    hovered && pointer.just_pressed()
+}
+
+/// Draws the "Host private game" button, returns true when clicked
+pub fn draw_host_private_button() -> bool {
+    let button_width = (bounds::screen().w() - GAME_PADDING * 3) / 2;
+    let button_height = FONT_HEIGHT + GAME_PADDING;
+    draw_button(
+        GAME_PADDING,
+        MENU_ROWS_Y,
+        button_width,
+        button_height,
+        "Host private",
+        NEGATIVE_BG_COLOR
+    )
+}
+
+/// Draws the "Join by code" button, returns true when clicked
+pub fn draw_join_by_code_button() -> bool {
+    let button_width = (bounds::screen().w() - GAME_PADDING * 3) / 2;
+    let button_height = FONT_HEIGHT + GAME_PADDING;
+    draw_button(
+        GAME_PADDING * 2 + button_width,
+        MENU_ROWS_Y,
+        button_width,
+        button_height,
+        "Join by code",
+        NEGATIVE_BG_COLOR
+    )
+}
+
+/// The daily dungeon buttons on the menu
+pub enum DailyButton {
+    Play,
+    Results,
+}
+
+/// Draws the daily dungeon buttons above the title, returns the one clicked if any
+pub fn draw_daily_buttons() -> Option<DailyButton> {
+    let button_width = (bounds::screen().w() - GAME_PADDING * 3) / 2;
+    let button_height = FONT_HEIGHT + GAME_PADDING;
+    let play = draw_button(
+        GAME_PADDING,
+        DAILY_ROW_Y,
+        button_width,
+        button_height,
+        "Daily dungeon",
+        POSITIVE_BG_COLOR
+    );
+    let results = draw_button(
+        GAME_PADDING * 2 + button_width,
+        DAILY_ROW_Y,
+        button_width,
+        button_height,
+        "Daily results",
+        0x222222ff
+    );
+    if play {
+        Some(DailyButton::Play)
+    } else if results {
+        Some(DailyButton::Results)
     } else {
-        ["Press SPACE to start"]
+        None
+    }
+}
+
+/// Draws the day's daily dungeon results, best first: wins, then fewest turns, then least
+/// damage taken. The user's own result is highlighted.
+pub fn draw_daily_results_screen(day: u64, results: &[DailyResult], user_id: &str, frame: usize) {
+    draw_menu(true, frame, None, "Press SPACE to return to menu");
+
+    let mut ranked: Vec<&DailyResult> = results.iter().collect();
+    ranked.sort_by_key(|result| (!result.won, result.turns_taken, result.damage_taken));
+
+    let (year, month, day_of_month) = date_from_day(day);
+    let mut text = format!("Daily dungeon {}-{:02}-{:02}", year, month, day_of_month);
+    if ranked.is_empty() {
+        text.push_str("\n\nNobody made it out yet");
+    }
+    for (rank, result) in ranked.iter().take(MAX_DAILY_RESULTS).enumerate() {
+        let name: String = if result.user_id == user_id {
+            "You".to_string()
+        } else {
+            result.user_id.chars().take(8).collect()
+        };
+        text.push_str(
+            &format!(
+                "\n\n{}. {:<8} {:<4} {:>3} turns {:>3} dmg",
+                rank + 1,
+                name,
+                if result.won { "Won" } else { "Lost" },
+                result.turns_taken,
+                result.damage_taken
+            )
+        );
+    }
+
+    let own_rank = ranked.iter().position(|result| result.user_id == user_id);
+    let fill_color = match own_rank {
+        Some(rank) if rank < MAX_DAILY_RESULTS => POSITIVE_BG_COLOR,
+        _ => 0x222222ff,
     };
-    if let Some(item) = menu_items.first() {
-        draw_text(item, false);
+    draw_text_box(
+        GAME_PADDING as f32,
+        DAILY_RESULTS_Y as f32,
+        bounds::screen().w() - GAME_PADDING * 2,
+        (MAX_DAILY_RESULTS as u32 + 1) * FONT_HEIGHT * 2 + GAME_PADDING,
+        &text,
+        0xffffffff,
+        fill_color
+    );
+}
+
+/// Draws the room code typed so far, with the reason the last join failed if any
+pub fn draw_code_input(code: &str, join_error: Option<JoinRejectReason>) {
+    let canvas_width = bounds::screen().w();
+    let error = match join_error {
+        Some(JoinRejectReason::RoomNotFound) => "\n\nNo room with that code",
+        Some(JoinRejectReason::RoomFull) => "\n\nThat room is full",
+        None => "",
+    };
+    let text = format!("Enter code: {}_{}", code, error);
+    draw_text_box(
+        GAME_PADDING as f32,
+        MENU_ROWS_Y as f32,
+        canvas_width - GAME_PADDING * 2,
+        45,
+        &text,
+        0xffffffff,
+        0x222222ff
+    );
+}
+
+/// Draws the listed rooms below the title and returns the room that was clicked.
+/// Full rooms can still be watched.
+pub fn draw_room_list(rooms: &[RoomInfo]) -> Option<RoomInfo> {
+    let canvas_width = bounds::screen().w();
+    let row_width = canvas_width - GAME_PADDING * 2;
+    let row_height = FONT_HEIGHT + GAME_PADDING;
+    let list_y = MENU_ROWS_Y + row_height + 4; // Below the private game buttons
+
+    // Rooms with a free seat come first
+    let mut listed_rooms: Vec<&RoomInfo> = rooms.iter().collect();
+    listed_rooms.sort_by_key(|room| room.is_full());
+    listed_rooms.truncate(MAX_LISTED_ROOMS);
+    if listed_rooms.is_empty() {
+        draw_text_box(
+            GAME_PADDING as f32,
+            list_y as f32,
+            row_width,
+            row_height,
+            "No rooms yet",
+            0xffffffff,
+            0x222222ff
+        );
+        return None;
+    }
+
+    let mut clicked = None;
+    for (i, room) in listed_rooms.iter().enumerate() {
+        let row_y = list_y + (i as u32) * (row_height + 4);
+        let (action, fill_color) = if room.is_full() {
+            ("Watch", 0x555555ff)
+        } else {
+            ("Join", POSITIVE_BG_COLOR)
+        };
+        let kind = match room.mode {
+            GameMode::Multiplayer => "room",
+            GameMode::Daily => "daily",
+        };
+        let label = format!(
+            "{} {} {} {} ({}/{})",
+            action,
+            kind,
+            room.room_id,
+            room.board_size,
+            room.players.len(),
+            room.player_count
+        );
+        if draw_button(GAME_PADDING, row_y, row_width, row_height, &label, fill_color) {
+            clicked = Some((*room).clone());
+        }
     }
+    clicked
 }
 
-pub fn draw_turn_label(is_my_turn: bool, _game_state: &crate::GameState) {
+pub fn draw_turn_label(is_my_turn: bool, game_state: &crate::GameState) {
     let canvas_bounds = bounds::screen();
     let canvas_width = canvas_bounds.w();
     let canvas_height = canvas_bounds.h();
-    let turn_label = if is_my_turn { "It's your turn!" } else { "Please wait for your turn..." };
-    draw_text(turn_label, is_my_turn);
+    let turn_label = if is_my_turn {
+        "It's your turn!".to_string()
+    } else {
+        match turn_player_label(game_state) {
+            Some(turn_player) => format!("Please wait for {}...", turn_player),
+            None => "Please wait for your turn...".to_string(),
+        }
+    };
+    draw_text(&turn_label, is_my_turn);
+    let keys = game_state.get_local_player().map_or(0, |player| player.keys);
+    let objective = format!("Escape or kill the monster!  Keys: {}", keys);
     draw_text_box(
         GAME_PADDING as f32,
         (canvas_height - FONT_HEIGHT * 2 - GAME_PADDING * 4) as f32,
         canvas_width - GAME_PADDING * 2,
         FONT_HEIGHT + GAME_PADDING,
-        "Kill the monster to win!",
+        &objective,
         0xffffffff,
         POSITIVE_BG_COLOR
     );
     if is_my_turn {
-        draw_end_turn_button();
+        draw_end_turn_button(&game_state.room_id);
+    }
+}
+
+/// Draws the read-only status bar for spectators, with a button to take a free seat
+pub fn draw_spectator_label(game_state: &crate::GameState) {
+    let label = match turn_player_label(game_state) {
+        Some(turn_player) => format!("Watching: turn of {}", turn_player),
+        None => "Watching: waiting for players...".to_string(),
+    };
+
+    if !game_state.has_empty_seat() {
+        draw_text(&label, false);
+        return;
+    }
+
+    draw_text(&label, true);
+    let button_height = FONT_HEIGHT + GAME_PADDING;
+    let button_x = bounds::screen().w() - BUTTON_WIDTH - GAME_PADDING;
+    let button_y = bounds::screen().h() - button_height - GAME_PADDING;
+    let clicked = draw_button(
+        button_x,
+        button_y,
+        BUTTON_WIDTH,
+        button_height,
+        "Take seat",
+        POSITIVE_BG_COLOR
+    );
+    if clicked {
+        send_take_seat(&game_state.room_id);
+    }
+}
+
+/// The player whose turn it is with the size of their hand, which this client is only sent
+/// as a count, e.g. "Player2 (4 cards)"
+fn turn_player_label(game_state: &crate::GameState) -> Option<String> {
+    let turn = game_state.current_turn.as_ref()?;
+    let player_id = game_state.user_id_to_player_id.get(&turn.player_id)?;
+    let player = game_state.players.iter().find(|player| &player.id == player_id)?;
+    Some(format!("{} ({} cards)", player_id, player.hidden_hand_count))
+}
+
+/// Draws the room before the match starts: who sits in which seat and who is ready.
+/// Seated players toggle ready with the button or SPACE, spectators can take an open seat.
+pub fn draw_lobby(game_state: &crate::GameState) {
+    let canvas_width = bounds::screen().w();
+    let row_width = canvas_width - GAME_PADDING * 2;
+    let row_height = 36 + GAME_PADDING; // Room for a baby sprite and two lines of text
+    let title = if game_state.is_private_room {
+        format!("Private room {}\n\nShare the code with your friends", game_state.room_id)
+    } else if game_state.mode == GameMode::Daily {
+        format!("Daily dungeon {}\n\nSame dungeon for everyone today", game_state.room_id)
+    } else {
+        format!("Room {}", game_state.room_id)
+    };
+    draw_text_box(
+        GAME_PADDING as f32,
+        LOBBY_TITLE_Y as f32,
+        row_width,
+        45,
+        &title,
+        0xffffffff,
+        0x222222ff
+    );
+
+    for seat in 0..game_state.player_count {
+        let row_y = LOBBY_SEATS_Y + (seat as u32) * (row_height + 4);
+        let user_id = game_state.in_lobby
+            .get(seat)
+            .filter(|user_id| !user_id.is_empty());
+        let (label, fill_color) = match user_id {
+            Some(user_id) => {
+                let name = if *user_id == game_state.user {
+                    "You".to_string()
+                } else {
+                    user_id.chars().take(8).collect()
+                };
+                if game_state.ready.contains(user_id) {
+                    (format!("{}\n\nReady", name), POSITIVE_BG_COLOR)
+                } else {
+                    (format!("{}\n\nNot ready", name), 0x222222ff)
+                }
+            }
+            None => ("Open seat".to_string(), 0x555555ff),
+        };
+        draw_text_box(
+            GAME_PADDING as f32,
+            row_y as f32,
+            row_width,
+            row_height,
+            &label,
+            0xffffffff,
+            fill_color
+        );
+
+        // Show the colour of the baby this seat plays as
+        if let Some(player_id) = crate::PlayerId::from_index(seat) {
+            sprite!(
+                player_id.sprite_name(),
+                x = (canvas_width - GAME_PADDING - 36 - 8) as i32,
+                y = (row_y + GAME_PADDING / 2) as i32,
+                w = 36,
+                h = 36,
+                tx = 0,
+                ty = 0,
+                cover = false
+            );
+        }
+    }
+
+    if !game_state.in_lobby.contains(&game_state.user) {
+        draw_spectator_label(game_state);
+        return;
+    }
+
+    let is_ready = game_state.ready.contains(&game_state.user);
+    let hint = if is_ready { "Waiting for everyone..." } else { "Ready up to start" };
+    draw_text(hint, true);
+    let button_height = FONT_HEIGHT + GAME_PADDING;
+    let button_x = bounds::screen().w() - BUTTON_WIDTH - GAME_PADDING;
+    let button_y = bounds::screen().h() - button_height - GAME_PADDING;
+    let (label, fill_color) = if is_ready {
+        ("Not ready", NEGATIVE_BG_COLOR)
+    } else {
+        ("Ready", POSITIVE_BG_COLOR)
+    };
+    if draw_button(button_x, button_y, BUTTON_WIDTH, button_height, label, fill_color) {
+        send_set_ready(&game_state.room_id, !is_ready);
     }
 }
 
 /// Draws a waiting message if no player is connected.
-pub fn draw_waiting_for_players(_game_state: &crate::GameState) {
-    draw_text("Waiting for players...", false);
+/// Hosts of a private room see the room code to share instead.
+pub fn draw_waiting_for_players(game_state: &crate::GameState) {
+    if !game_state.reconnecting.is_empty() {
+        draw_text("Waiting for a player to reconnect...", false);
+    } else if game_state.is_private_room {
+        draw_menu(false, game_state.frame, Some(&game_state.room_id), "Waiting for your friend...");
+    } else {
+        let text = format!(
+            "Waiting for players... ({}/{})",
+            game_state.seated_count(),
+            game_state.player_count
+        );
+        draw_text(&text, false);
+    }
+}
+
+/// Draws the screen shown when the server turned down this client's protocol version
+pub fn draw_refresh_screen(reason: IncompatibleReason, frame: usize) {
+    draw_menu(false, frame, None, "Please refresh the page");
+    let text = match reason {
+        IncompatibleReason::ClientOutdated => "A new version is out\n\nRefresh to update the game",
+        IncompatibleReason::ServerOutdated => "The server is updating\n\nRefresh in a moment",
+    };
+    draw_text_box(
+        GAME_PADDING as f32,
+        MENU_ROWS_Y as f32,
+        bounds::screen().w() - GAME_PADDING * 2,
+        45,
+        text,
+        0xffffffff,
+        NEGATIVE_BG_COLOR
+    );
+}
+
+/// The buttons below a replay
+pub enum ReplayControl {
+    PlayPause,
+    Step,
+    Exit,
+}
+
+/// Draws how far the replay is and its controls, returns the control clicked if any
+pub fn draw_replay_controls(replay: &ReplayPlayer) -> Option<ReplayControl> {
+    let canvas_width = bounds::screen().w();
+    let button_width = (canvas_width - GAME_PADDING * 4) / 3;
+    let button_height = FONT_HEIGHT + GAME_PADDING;
+    let button_y = bounds::screen().h() - button_height - GAME_PADDING;
+
+    let status = if replay.is_finished() {
+        format!("Replay of seed {}: finished", replay.replay.seed())
+    } else {
+        format!(
+            "Replay of seed {}: step {}/{}",
+            replay.replay.seed(),
+            replay.next_step,
+            replay.replay.steps.len()
+        )
+    };
+    draw_text_box(
+        GAME_PADDING as f32,
+        (button_y - button_height - 4) as f32,
+        canvas_width - GAME_PADDING * 2,
+        button_height,
+        &status,
+        0xffffffff,
+        0x222222ff
+    );
+
+    let buttons = [
+        (if replay.playing { "Pause" } else { "Play" }, ReplayControl::PlayPause),
+        ("Step", ReplayControl::Step),
+        ("Exit", ReplayControl::Exit),
+    ];
+    let mut clicked = None;
+    for (i, (label, control)) in buttons.into_iter().enumerate() {
+        let button_x = GAME_PADDING + (i as u32) * (button_width + GAME_PADDING);
+        let fill_color = if i == 2 { NEGATIVE_BG_COLOR } else { POSITIVE_BG_COLOR };
+        if draw_button(button_x, button_y, button_width, button_height, label, fill_color) {
+            clicked = Some(control);
+        }
+    }
+    clicked
 }
 
-/// Draws the game over screen with winner/loser information
-pub fn draw_game_over_screen(winner_ids: &[String], loser_ids: &[String], frame: usize) {
-    draw_menu(true, frame);
+/// Draws the game over screen with winner/loser information, returns true when the replay
+/// button was clicked
+pub fn draw_game_over_screen(
+    winner_ids: &[String],
+    loser_ids: &[String],
+    outcome: MatchOutcome,
+    frame: usize
+) -> bool {
+    draw_menu(true, frame, None, "Press SPACE to return to menu");
     let canvas_bounds = bounds::screen();
     let canvas_width = canvas_bounds.w();
     let canvas_height = canvas_bounds.h();
@@ -177,10 +630,14 @@ pub fn draw_game_over_screen(winner_ids: &[String], loser_ids: &[String], frame:
 
     // Determine title and color based on game outcome
     let (title, fill_color) = if !winner_ids.is_empty() && loser_ids.is_empty() {
-        // Cooperative victory (both players win)
-        ("VICTORY!\n\nYou defeated the monster together!", POSITIVE_BG_COLOR)
+        // Cooperative victory (all players win)
+        let title = match outcome {
+            MatchOutcome::Escaped => "VICTORY!\n\nYour babies escaped the dungeon!",
+            _ => "VICTORY!\n\nYou defeated the monster together!",
+        };
+        (title, POSITIVE_BG_COLOR)
     } else if winner_ids.is_empty() && !loser_ids.is_empty() {
-        // Cooperative loss (both players lose)
+        // Cooperative loss (all players lose)
         ("DEFEAT\n\nYou were defeated by the monster", NEGATIVE_BG_COLOR)
     } else {
         // Fallback for unexpected states
@@ -196,4 +653,13 @@ pub fn draw_game_over_screen(winner_ids: &[String], loser_ids: &[String], frame:
         0xffffffff, // White text
         fill_color // Game background color
     );
+
+    draw_button(
+        GAME_PADDING,
+        MENU_ROWS_Y,
+        box_width,
+        FONT_HEIGHT + GAME_PADDING,
+        "Watch replay",
+        0x222222ff
+    )
 }
@@ -8,3 +8,19 @@ pub fn point_in_bounds(x: i32, y: i32, bounds: &Bounds) -> bool {
 
     x >= bx && x < bx + bw && y >= by && y < by + bh
 }
+
+/// The calendar date (year, month, day) of a day counted from the Unix epoch
+pub fn date_from_day(day: u64) -> (u64, u32, u32) {
+    // Counts from 0000-03-01 so leap days fall at the end of the year
+    let days = day + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153; // March is 0
+    let day_of_month = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month as u32, day_of_month as u32)
+}
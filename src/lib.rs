@@ -1,26 +1,63 @@
 mod server;
-use crate::server::{ GameChannel, CurrentTurn, ServerToClient };
+use crate::server::{ GameChannel, LobbyChannel, CurrentTurn };
+mod protocol;
+use crate::protocol::{
+    ServerToClient,
+    GameEvent,
+    BoardSnapshot,
+    LobbyToClient,
+    RoomInfo,
+    JoinRejectReason,
+    IncompatibleReason,
+};
 mod game;
 mod scene;
 mod network;
+mod rules;
 
 use crate::game::map::{ draw_board, clear_highlights };
-use crate::game::constants::{ DEBUG_MODE, GAME_PADDING, HAND_SIZE, MAP_SIZE, GAME_CHANNEL };
-use crate::game::inputs::{ handle_input, handle_reset_game };
-use crate::game::map::{ Player, PlayerId, Monster };
+use crate::game::constants::{
+    DEBUG_MODE,
+    GAME_PADDING,
+    HAND_SIZE,
+    LOBBY_CHANNEL,
+    ROOM_CODE_LENGTH,
+    MIN_PLAYERS,
+    MAX_PLAYERS,
+    HELLO_RETRY_FRAMES,
+    REPLAY_STEP_FRAMES,
+};
+use crate::game::inputs::{ handle_input, handle_reset_game, handle_seed_input };
+use crate::game::map::{ Player, PlayerId, Monster, BoardSize };
 use crate::game::map::Tile;
 use crate::game::ui::{
     draw_turn_label,
+    draw_spectator_label,
     draw_waiting_for_players,
+    draw_lobby,
+    draw_refresh_screen,
     draw_menu,
+    draw_room_list,
+    draw_host_private_button,
+    draw_join_by_code_button,
+    draw_code_input,
     draw_game_over_screen,
+    draw_seed_label,
+    draw_daily_buttons,
+    draw_daily_results_screen,
+    draw_replay_controls,
+    DailyButton,
+    ReplayControl,
 };
+use crate::game::replay::ReplayPlayer;
+use crate::rules::{ Replay, MatchOutcome };
 use crate::game::animation::{
     update_animations,
     AnimatedCard,
     AnimatedPlayer,
     AnimatedTile,
     AnimatedFireball,
+    PendingFireballHit,
 };
 use crate::game::debug::draw_debug;
 use crate::game::cards::{ draw_play_area, draw_hand };
@@ -33,16 +70,48 @@ use crate::network::receive::{
     receive_card_confirmed,
     receive_tile_rotation,
     receive_player_moved,
+    receive_action_rejected,
     receive_tiles_swapped,
     receive_fireball_shot,
     receive_fireball_hit_result,
     receive_player_damage_from_monster,
+    receive_key_picked_up,
+    receive_door_unlocked,
+    receive_player_burned,
+    receive_player_healed,
+    receive_player_teleported,
+    receive_treasure_found,
     receive_game_over,
     receive_reset_game,
+    receive_start_game,
+    receive_board_delta,
+    receive_hello_accepted,
+    receive_lobby_hello_accepted,
+    receive_incompatible,
+    receive_seat_assignment,
+    receive_room_list,
+    receive_room_joined,
+    receive_join_rejected,
+    receive_daily_results,
+    receive_replay,
+};
+use crate::network::send::{
+    send_create_room,
+    send_join_room,
+    send_watch_room,
+    send_leave_room,
+    send_configure_match,
+    send_set_ready,
+    send_hello,
+    send_lobby_hello,
+    send_request_snapshot,
+    send_request_daily_results,
+    send_request_replay,
 };
+use crate::network::event_queue::{ EventQueue, QueuedUpdate };
 
 use turbo::{ os, gamepad, bounds, * };
-use scene::Scene;
+use scene::{ Scene, GameMode };
 
 use std::fmt;
 use std::collections::HashMap;
@@ -52,6 +121,8 @@ impl fmt::Display for PlayerId {
         match self {
             PlayerId::Player1 => write!(f, "Player1"),
             PlayerId::Player2 => write!(f, "Player2"),
+            PlayerId::Player3 => write!(f, "Player3"),
+            PlayerId::Player4 => write!(f, "Player4"),
         }
     }
 }
@@ -66,6 +137,22 @@ pub struct GameState {
     pub scene: Scene, // Track current scene (menu or game)
     pub user: String, // This client's user id
     pub in_lobby: Vec<String>, // Users in the current game lobby
+    pub spectators: Vec<String>, // Users watching the current game without a seat
+    pub reconnecting: Vec<String>, // Seated users the server is holding a seat for
+    pub ready: Vec<String>, // Seated users who readied up in the room lobby
+    pub room_id: String, // Game room this client is in, empty while in the menu
+    pub rooms: Vec<RoomInfo>, // Rooms listed by the lobby
+    pub is_private_room: bool,
+    pub mode: GameMode, // Mode of the room this client is in
+    pub player_count: usize, // Players needed to start a match, picked in the menu
+    pub board_size: BoardSize, // Picked in the menu, then whatever the room's match is played on
+    pub code_input: Option<String>, // Room code being typed in the menu, if any
+    pub seed: u64, // Seed of the match on screen, so it can be shared and played again
+    pub seed_input: Option<String>, // Seed being typed for the next reset, if any
+    pub join_error: Option<JoinRejectReason>, // Why the last join attempt failed
+    pub lobby_greeted: bool, // The lobby accepted our Hello
+    pub room_greeted: bool, // The current room's game channel accepted our Hello
+    pub incompatible: Option<IncompatibleReason>, // Set when the server turned down our Hello
     pub debug: bool,
     pub user_id_to_player_id: HashMap<String, PlayerId>,
     pub animated_card: Option<AnimatedCard>,
@@ -73,10 +160,13 @@ pub struct GameState {
     pub animated_tiles: Vec<AnimatedTile>, // Track multiple tile animations
     pub play_area: Vec<Card>,
     pub current_turn: Option<CurrentTurn>,
+    pub events: EventQueue, // Server events waiting to be applied in order
     pub swap_tiles_selected: Vec<usize>, // Track tiles selected for swapping
     pub pending_swaps: Vec<(usize, usize)>, // Track tiles that will be swapped when animation completes
     pub fireballs: Vec<crate::game::map::fireball::Fireball>,
     pub animated_fireballs: Vec<AnimatedFireball>,
+    pub pending_fireball_hit: Option<PendingFireballHit>, // Applied when the fireball animation lands
+    pub replay: Option<ReplayPlayer>, // The match being played back in the replay scene
 }
 
 impl GameState {
@@ -91,6 +181,22 @@ impl GameState {
             scene: Scene::Menu, // Start in menu scene
             user: String::new(), // Will be set on connect
             in_lobby: Vec::new(),
+            spectators: Vec::new(),
+            reconnecting: Vec::new(),
+            ready: Vec::new(),
+            room_id: String::new(),
+            rooms: Vec::new(),
+            is_private_room: false,
+            mode: GameMode::Multiplayer,
+            player_count: MIN_PLAYERS,
+            board_size: BoardSize::default(),
+            code_input: None,
+            seed: 0,
+            seed_input: None,
+            join_error: None,
+            lobby_greeted: false,
+            room_greeted: false,
+            incompatible: None,
             user_id_to_player_id: HashMap::new(),
             animated_card: None,
             animated_player: None,
@@ -101,10 +207,13 @@ impl GameState {
                 play_area
             },
             current_turn: None,
+            events: EventQueue::default(),
             swap_tiles_selected: Vec::new(),
             pending_swaps: Vec::new(),
             fireballs: Vec::new(),
             animated_fireballs: Vec::new(),
+            pending_fireball_hit: None,
+            replay: None,
         }
     }
 
@@ -115,6 +224,24 @@ impl GameState {
         self.current_turn.as_ref().map_or(false, |turn| self.user == turn.player_id)
     }
 
+    /// Returns true if this user is watching the game without a seat
+    pub fn is_spectator(&self) -> bool {
+        self.spectators.contains(&self.user)
+    }
+
+    /// Number of seats in the room that have a connected user
+    pub fn seated_count(&self) -> usize {
+        self.in_lobby
+            .iter()
+            .filter(|user| !user.is_empty())
+            .count()
+    }
+
+    /// Returns true if a seat is free for a spectator to take
+    pub fn has_empty_seat(&self) -> bool {
+        self.seated_count() < self.player_count
+    }
+
     /// Helper to get the current turn player by ID (returns Option<&Player>)
     fn get_turn_player(&self) -> Option<&Player> {
         let user_id = self.current_turn.as_ref()?.player_id.as_str();
@@ -145,13 +272,26 @@ impl GameState {
         self.players.iter_mut().find(|p| &p.id == player_id)
     }
 
-    /// Returns (canvas_width, canvas_height, tile_size, offset_x, offset_y) for the board layout
+    /// Returns true while the board is still playing out an event
+    pub fn is_board_animating(&self) -> bool {
+        self.animated_player.is_some() ||
+            !self.animated_tiles.is_empty() ||
+            !self.pending_swaps.is_empty() ||
+            !self.animated_fireballs.is_empty() ||
+            self.pending_fireball_hit.is_some() ||
+            self.tiles.iter().any(|tile| tile.rotation_anim.is_some())
+    }
+
+    /// Returns (canvas_width, canvas_height, tile_size, offset_x, offset_y) for the board layout.
+    /// The board fits in a square as wide as the canvas, centred in it.
     pub fn get_board_layout(&self, padded: bool) -> (u32, u32, u32, u32, u32) {
         let canvas_width = bounds::screen().w() - (if padded { GAME_PADDING * 2 } else { 0 });
         let canvas_height = bounds::screen().h();
-        let tile_size = canvas_width / (MAP_SIZE as u32);
-        let offset_x = canvas_width / 2 - (tile_size * (MAP_SIZE as u32)) / 2;
-        let offset_y = 0;
+        let columns = self.board_size.width as u32;
+        let rows = self.board_size.height as u32;
+        let tile_size = (canvas_width / columns).min(canvas_width / rows);
+        let offset_x = canvas_width / 2 - (tile_size * columns) / 2;
+        let offset_y = canvas_width / 2 - (tile_size * rows) / 2;
         (canvas_width, canvas_height, tile_size, offset_x, offset_y)
     }
 
@@ -166,16 +306,32 @@ impl GameState {
         self.play_area.clear();
         self.fireballs.clear();
         self.animated_fireballs.clear();
+        self.pending_fireball_hit = None;
         fill_with_dummies(&mut self.play_area, HAND_SIZE);
     }
 
-    pub fn game_over_cooperative(&mut self, winner_ids: &[String], loser_ids: &[String]) {
+    /// Drops what belongs to the current match on this client: animations, selections and the
+    /// play area. The room, the user and the scene are kept; the board is replaced by the next
+    /// one the server sends.
+    pub fn reset_match(&mut self) {
+        self.animated_card = None;
+        self.animated_player = None;
+        self.reset_turn();
+    }
+
+    pub fn game_over_cooperative(
+        &mut self,
+        winner_ids: &[String],
+        loser_ids: &[String],
+        outcome: MatchOutcome
+    ) {
         self.reset_turn();
         self.players = Vec::new();
 
         self.scene = Scene::GameOver {
             winner_ids: winner_ids.to_vec(),
             loser_ids: loser_ids.to_vec(),
+            outcome,
         };
     }
 
@@ -185,117 +341,498 @@ impl GameState {
         sprite!("bg", x = 0, y = 0, w = canvas_width, h = canvas_height);
 
         self.frame += 1;
+        // Nothing from a server speaking another protocol can be trusted
+        if let Some(reason) = self.incompatible {
+            draw_refresh_screen(reason, self.frame);
+            return;
+        }
+
         update_animations(self);
         match &self.scene {
             Scene::Menu => self.update_menu(),
+            Scene::Lobby => self.update_lobby(),
             Scene::Game => self.update_game(),
-            Scene::GameOver { winner_ids, loser_ids } => {
+            Scene::GameOver { winner_ids, loser_ids, outcome } => {
                 let winner_ids = winner_ids.clone();
                 let loser_ids = loser_ids.clone();
-                self.update_game_over_cooperative(&winner_ids, &loser_ids);
+                let outcome = *outcome;
+                self.update_game_over_cooperative(&winner_ids, &loser_ids, outcome);
+            }
+            Scene::Replay => self.update_replay(),
+            Scene::DailyResults { day, results } => {
+                // The menu has no user id yet, ask the client for it
+                let user_id = os::client::user_id().unwrap_or_default();
+                draw_daily_results_screen(*day, results, &user_id, self.frame);
+                if gamepad::get(0).start.just_pressed() {
+                    self.scene = Scene::Menu;
+                }
             }
         }
         draw_debug(self);
-        handle_reset_game(self);
+        // Letters typed into a room code must not trigger the reset shortcut
+        if self.code_input.is_none() {
+            if !self.room_id.is_empty() {
+                handle_seed_input(self);
+            }
+            handle_reset_game(self);
+        }
+    }
+
+    /// Enters the game scene for a room the lobby has put this client in
+    pub fn enter_room(
+        &mut self,
+        room_id: String,
+        is_private: bool,
+        player_count: usize,
+        mode: GameMode,
+        board_size: BoardSize
+    ) {
+        self.room_id = room_id;
+        self.is_private_room = is_private;
+        self.player_count = player_count;
+        self.mode = mode;
+        self.board_size = board_size;
+        self.code_input = None;
+        self.join_error = None;
+        self.scene = Scene::Lobby;
+        self.user = os::client::user_id().unwrap_or_else(|| "NO_ID".to_string());
+        // Player ids come from the room's SeatAssignment messages
+        self.user_id_to_player_id.clear();
+        self.in_lobby.clear();
+        self.spectators.clear();
+        self.reconnecting.clear();
+        self.ready.clear();
+        self.room_greeted = false;
+        self.events = EventQueue::default();
+        send_configure_match(&self.room_id, player_count, mode, board_size);
     }
 
     fn update_menu(&mut self) {
-        draw_menu(false, self.frame);
-        if gamepad::get(0).start.just_pressed() {
-            self.scene = Scene::Game;
-            self.user = os::client::user_id().unwrap_or_else(|| "NO_ID".to_string());
-            // In a real game, you would get the mapping from the server or lobby
-            // For now, assign the first two users to Player1 and Player2 as a placeholder
-            self.user_id_to_player_id.clear();
-            if self.in_lobby.len() >= 2 {
-                self.user_id_to_player_id.insert(self.in_lobby[0].clone(), PlayerId::Player1);
-                self.user_id_to_player_id.insert(self.in_lobby[1].clone(), PlayerId::Player2);
+        if !self.lobby_greeted && self.frame % HELLO_RETRY_FRAMES == 1 {
+            send_lobby_hello();
+        }
+        if let Some(conn) = LobbyChannel::subscribe(LOBBY_CHANNEL) {
+            while let Ok(msg) = conn.recv() {
+                match msg {
+                    LobbyToClient::HelloAccepted { protocol_version } => {
+                        receive_lobby_hello_accepted(self, protocol_version);
+                    }
+                    LobbyToClient::Incompatible { protocol_version, reason } => {
+                        receive_incompatible(self, protocol_version, reason);
+                        return;
+                    }
+                    LobbyToClient::RoomList { rooms } => {
+                        receive_room_list(self, rooms);
+                    }
+                    LobbyToClient::RoomJoined {
+                        room_id,
+                        is_private,
+                        player_count,
+                        mode,
+                        board_size,
+                    } => {
+                        receive_room_joined(
+                            self,
+                            room_id,
+                            is_private,
+                            player_count,
+                            mode,
+                            board_size
+                        );
+                    }
+                    LobbyToClient::JoinRejected { room_id, reason } => {
+                        receive_join_rejected(self, &room_id, &reason);
+                    }
+                    LobbyToClient::DailyResults { day, results } => {
+                        receive_daily_results(self, day, results);
+                        return;
+                    }
+                }
+            }
+        }
+
+        if self.code_input.is_some() {
+            draw_menu(false, self.frame, None, "Press ENTER to join, ESC to cancel");
+            self.update_code_input();
+            return;
+        }
+
+        let hint = format!(
+            "SPACE: create room  < {} players >  ^ {} v",
+            self.player_count,
+            self.board_size
+        );
+        draw_menu(false, self.frame, None, &hint);
+        let gp = gamepad::get(0);
+        if gp.left.just_pressed() && self.player_count > MIN_PLAYERS {
+            self.player_count -= 1;
+        }
+        if gp.right.just_pressed() && self.player_count < MAX_PLAYERS {
+            self.player_count += 1;
+        }
+        if gp.up.just_pressed() {
+            self.board_size = self.board_size.cycled(false);
+        }
+        if gp.down.just_pressed() {
+            self.board_size = self.board_size.cycled(true);
+        }
+        match draw_daily_buttons() {
+            Some(DailyButton::Play) => {
+                send_create_room(false, self.player_count, GameMode::Daily, BoardSize::default());
+            }
+            Some(DailyButton::Results) => send_request_daily_results(),
+            None => {}
+        }
+        if draw_host_private_button() {
+            send_create_room(true, self.player_count, GameMode::Multiplayer, self.board_size);
+        }
+        if draw_join_by_code_button() {
+            self.code_input = Some(String::new());
+            self.join_error = None;
+        }
+        if let Some(room) = draw_room_list(&self.rooms) {
+            if room.is_full() {
+                send_watch_room(&room.room_id);
+            } else {
+                send_join_room(&room.room_id);
+            }
+        }
+        if gp.start.just_pressed() {
+            send_create_room(false, self.player_count, GameMode::Multiplayer, self.board_size);
+        }
+    }
+
+    /// Handles typing a private room code in the menu
+    fn update_code_input(&mut self) {
+        let keyboard = keyboard::get();
+        let mut code = self.code_input.take().unwrap_or_default();
+
+        for c in keyboard.chars() {
+            if c.is_ascii_alphanumeric() && code.len() < ROOM_CODE_LENGTH {
+                code.push(c.to_ascii_uppercase());
+                self.join_error = None;
+            }
+        }
+        if keyboard.backspace().just_pressed() {
+            code.pop();
+        }
+        draw_code_input(&code, self.join_error);
+
+        if keyboard.enter().just_pressed() && code.len() == ROOM_CODE_LENGTH {
+            send_join_room(&code);
+        }
+        if keyboard.escape().just_pressed() {
+            self.join_error = None;
+        } else {
+            self.code_input = Some(code);
+        }
+    }
+
+    fn update_lobby(&mut self) {
+        self.receive_game_messages();
+        // StartGame moves everyone on to the game scene
+        if let Scene::Lobby = self.scene {
+            draw_lobby(self);
+            let is_seated = self.in_lobby.contains(&self.user);
+            if is_seated && gamepad::get(0).start.just_pressed() {
+                send_set_ready(&self.room_id, !self.ready.contains(&self.user));
             }
         }
     }
 
     fn update_game(&mut self) {
-        if let Some(conn) = GameChannel::subscribe(GAME_CHANNEL) {
+        self.receive_game_messages();
+        self.draw_game();
+
+        if self.is_my_turn() {
+            handle_input(self);
+        }
+    }
+
+    /// Applies every message the room's game channel has sent since the last frame
+    fn receive_game_messages(&mut self) {
+        if !self.room_greeted && self.frame % HELLO_RETRY_FRAMES == 1 {
+            send_hello(&self.room_id);
+        }
+        if let Some(conn) = GameChannel::subscribe(&self.room_id) {
             while let Ok(msg) = conn.recv() {
                 match msg {
-                    ServerToClient::ConnectedUsers { users } => {
-                        receive_connected_users(self, users);
+                    ServerToClient::HelloAccepted { protocol_version } => {
+                        receive_hello_accepted(self, protocol_version);
                     }
 
-                    ServerToClient::ResetGame => {
-                        receive_reset_game(self);
+                    ServerToClient::Incompatible { protocol_version, reason } => {
+                        receive_incompatible(self, protocol_version, reason);
+                        return;
                     }
 
-                    ServerToClient::BoardState { tiles, players, monster, current_turn } => {
-                        receive_board_state(self, tiles, players, monster, current_turn);
+                    ServerToClient::ConnectedUsers { users, spectators, reconnecting, ready } => {
+                        receive_connected_users(self, users, spectators, reconnecting, ready);
                     }
 
-                    ServerToClient::CardCancelled { card, player_id, board_tiles } => {
-                        receive_card_cancelled(self, &card, &player_id, &board_tiles);
+                    ServerToClient::SeatAssignment { player_ids } => {
+                        receive_seat_assignment(self, player_ids);
                     }
 
-                    ServerToClient::CardConfirmed { card, player_id } => {
-                        receive_card_confirmed(self, &card, &player_id);
+                    ServerToClient::StartGame => {
+                        receive_start_game(self);
                     }
 
-                    ServerToClient::TileRotated { tile_index, tile, player_id } => {
-                        receive_tile_rotation(self, &tile_index, &tile, &player_id);
+                    ServerToClient::BoardState {
+                        seq,
+                        seed,
+                        board_size,
+                        tiles,
+                        players,
+                        monster,
+                        current_turn,
+                    } => {
+                        let board = BoardSnapshot {
+                            seed,
+                            board_size,
+                            tiles,
+                            players,
+                            monster,
+                            current_turn,
+                        };
+                        self.events.push_snapshot(seq, board);
                     }
 
-                    ServerToClient::PlayerMoved { player_id, new_position, is_canceled } => {
-                        receive_player_moved(self, &player_id, &new_position, is_canceled);
+                    ServerToClient::Event { seq, event } => {
+                        self.events.push_event(seq, event);
                     }
 
-                    ServerToClient::TilesSwapped { tile_index_1, tile_index_2 } => {
-                        receive_tiles_swapped(self, &tile_index_1, &tile_index_2);
+                    ServerToClient::ActionRejected { reason, request } => {
+                        receive_action_rejected(self, &reason, &request);
                     }
 
-                    ServerToClient::FireballShot { player_id, tile_index, direction } => {
-                        receive_fireball_shot(self, &player_id, &tile_index, &direction);
-                    }
-                    ServerToClient::FireballHit {
-                        player_id,
-                        target_id,
-                        damage_dealt,
-                        monster_damage,
-                    } => {
-                        receive_fireball_hit_result(
-                            self,
-                            &player_id,
-                            &target_id,
-                            &damage_dealt,
-                            monster_damage
-                        );
-                    }
-                    ServerToClient::PlayerDamageFromMonster { player_id, damage_dealt } => {
-                        receive_player_damage_from_monster(self, &player_id, damage_dealt);
-                    }
-                    ServerToClient::GameOver { winner_ids, loser_ids } => {
-                        receive_game_over(self, &winner_ids, &loser_ids);
+                    ServerToClient::Replay { replay } => {
+                        receive_replay(self, replay);
+                        return;
                     }
                 }
             }
+        }
+        self.apply_queued_events();
+    }
+
+    /// Applies queued server events in order, waiting for each one's animation to finish
+    /// before the next. A missing event is filled in by asking the server for the board.
+    fn apply_queued_events(&mut self) {
+        while !self.is_board_animating() {
+            match self.events.pop() {
+                Some(QueuedUpdate::Event(event)) => self.apply_event(event),
+                Some(QueuedUpdate::Snapshot(board)) => {
+                    receive_board_state(
+                        self,
+                        board.seed,
+                        board.board_size,
+                        board.tiles,
+                        board.players,
+                        board.monster,
+                        board.current_turn
+                    );
+                }
+                None => break,
+            }
+        }
+
+        if self.events.has_gap() {
+            log!(
+                "📨 [RECEIVE] Missed the event after {}, requesting a snapshot",
+                self.events.last_seq
+            );
+            self.events.snapshot_requested = true;
+            send_request_snapshot(&self.room_id);
+        }
+    }
+
+    fn apply_event(&mut self, event: GameEvent) {
+        // A replay shows the local player's recorded actions too, none were played here
+        let from_replay = matches!(self.scene, Scene::Replay);
+        match event {
+            GameEvent::ResetGame => {
+                receive_reset_game(self);
+            }
 
-            self.draw_game();
+            GameEvent::BoardDelta { changes } => {
+                receive_board_delta(self, changes);
+            }
 
-            if self.is_my_turn() {
-                handle_input(self);
+            GameEvent::CardCancelled { card, player_id } => {
+                receive_card_cancelled(self, &card, &player_id, from_replay);
+            }
+
+            GameEvent::CardConfirmed { card, player_id } => {
+                receive_card_confirmed(self, &card, &player_id);
+            }
+
+            GameEvent::TileRotated { tile_index, tile, player_id } => {
+                receive_tile_rotation(self, &tile_index, &tile, &player_id, from_replay);
+            }
+
+            GameEvent::PlayerMoved { player_id, new_position, is_canceled } => {
+                receive_player_moved(
+                    self,
+                    &player_id,
+                    &new_position,
+                    is_canceled,
+                    from_replay
+                );
+            }
+
+            GameEvent::TilesSwapped { tile_index_1, tile_index_2 } => {
+                receive_tiles_swapped(self, &tile_index_1, &tile_index_2);
+            }
+
+            GameEvent::FireballShot {
+                player_id,
+                tile_index,
+                direction,
+                end_tile_index,
+                hits_target,
+            } => {
+                receive_fireball_shot(
+                    self,
+                    &player_id,
+                    &tile_index,
+                    &direction,
+                    &end_tile_index,
+                    hits_target
+                );
+            }
+            GameEvent::FireballHit { player_id, target_id, damage_dealt, monster_damage } => {
+                receive_fireball_hit_result(
+                    self,
+                    &player_id,
+                    &target_id,
+                    &damage_dealt,
+                    monster_damage
+                );
+            }
+            GameEvent::PlayerDamageFromMonster { player_id, damage_dealt } => {
+                receive_player_damage_from_monster(self, &player_id, damage_dealt);
+            }
+            GameEvent::KeyPickedUp { player_id, tile_index } => {
+                receive_key_picked_up(self, &player_id, tile_index);
+            }
+            GameEvent::DoorUnlocked { player_id, tile_index, direction } => {
+                receive_door_unlocked(self, &player_id, tile_index, direction);
+            }
+            GameEvent::PlayerBurned { player_id, damage_dealt } => {
+                receive_player_burned(self, &player_id, damage_dealt);
+            }
+            GameEvent::PlayerHealed { player_id, amount } => {
+                receive_player_healed(self, &player_id, amount);
+            }
+            GameEvent::PlayerTeleported { player_id, new_position } => {
+                receive_player_teleported(self, &player_id, new_position);
+            }
+            GameEvent::TreasureFound { player_id, tile_index, card } => {
+                receive_treasure_found(self, &player_id, tile_index, card);
+            }
+            GameEvent::GameOver { winner_ids, loser_ids, outcome } => {
+                receive_game_over(self, &winner_ids, &loser_ids, outcome);
             }
         }
     }
 
-    fn update_game_over_cooperative(&mut self, winner_ids: &[String], loser_ids: &[String]) {
+    fn update_game_over_cooperative(
+        &mut self,
+        winner_ids: &[String],
+        loser_ids: &[String],
+        outcome: MatchOutcome
+    ) {
         // Draw the game over screen using our UI function
-        draw_game_over_screen(winner_ids, loser_ids, self.frame);
+        if draw_game_over_screen(winner_ids, loser_ids, outcome, self.frame) {
+            send_request_replay(&self.room_id);
+        }
+        draw_seed_label(self.seed, self.seed_input.as_deref());
+
+        // The room keeps talking: the replay we asked for, or a new match another player reset
+        self.receive_game_messages();
+        if !matches!(self.scene, Scene::GameOver { .. }) {
+            return;
+        }
 
         // Allow players to return to menu
         if gamepad::get(0).start.just_pressed() {
-            self.scene = Scene::Menu;
-            self.user_id_to_player_id.clear();
-            self.in_lobby.clear();
+            self.leave_room();
         }
     }
 
+    /// Leaves the room for the menu
+    fn leave_room(&mut self) {
+        send_leave_room();
+        self.scene = Scene::Menu;
+        self.room_id.clear();
+        self.user_id_to_player_id.clear();
+        self.in_lobby.clear();
+        self.replay = None;
+    }
+
+    /// Starts playing a recorded match back on the board
+    pub fn start_replay(&mut self, replay: Replay) {
+        let exit_scene = self.scene.clone();
+        self.events = EventQueue::default();
+        self.replay = Some(ReplayPlayer::new(replay, exit_scene, &mut self.events));
+        self.scene = Scene::Replay;
+    }
+
+    /// Stops the replay and goes back to the screen it was started from, still in the room
+    fn exit_replay(&mut self) {
+        if let Some(replay) = self.replay.take() {
+            self.events = EventQueue::default();
+            self.reset_match();
+            self.scene = replay.exit_scene;
+        }
+    }
+
+    /// Steps through the replay on its own while playing, or one step per click when paused.
+    /// A step only starts once the board is done animating the one before it.
+    fn update_replay(&mut self) {
+        let (_, _, tile_size, offset_x, offset_y) = self.get_board_layout(false);
+        draw_board(self, self.frame as f64, tile_size, offset_x, offset_y);
+
+        let gp = gamepad::get(0);
+        let clicked = self.replay.as_ref().and_then(draw_replay_controls);
+        let control = if gp.start.just_pressed() {
+            Some(ReplayControl::PlayPause)
+        } else if gp.right.just_pressed() {
+            Some(ReplayControl::Step)
+        } else {
+            clicked
+        };
+
+        let is_idle = !self.is_board_animating() && self.events.is_empty();
+        let frame = self.frame;
+        if let Some(replay) = &mut self.replay {
+            match control {
+                Some(ReplayControl::PlayPause) => {
+                    replay.playing = !replay.playing;
+                }
+                Some(ReplayControl::Step) => {
+                    replay.playing = false;
+                    if is_idle {
+                        replay.step(&mut self.events);
+                    }
+                }
+                Some(ReplayControl::Exit) | None => {}
+            }
+            if replay.playing && is_idle && frame >= replay.next_step_frame {
+                replay.step(&mut self.events);
+                replay.next_step_frame = frame + REPLAY_STEP_FRAMES;
+            }
+        }
+
+        if let Some(ReplayControl::Exit) = control {
+            self.exit_replay();
+            return;
+        }
+        // The last step ends the match, which brings the game over screen back
+        self.apply_queued_events();
+    }
+
     fn draw_game(&self) {
         // Get board layout parameters
         let (_, _, tile_size, offset_x, offset_y) = self.get_board_layout(false);
@@ -309,8 +846,15 @@ impl GameState {
             } else {
                 draw_waiting_for_players(self);
             }
+        } else if self.is_spectator() {
+            // Spectators see the board but have no hand or play area
+            if self.current_turn.is_some() {
+                draw_board(self, self.frame as f64, tile_size, offset_x, offset_y);
+            }
+            draw_spectator_label(self);
         } else {
             draw_waiting_for_players(self);
         }
+        draw_seed_label(self.seed, self.seed_input.as_deref());
     }
 }
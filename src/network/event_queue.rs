@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+use serde::{ Serialize, Deserialize };
+use borsh::{ BorshSerialize, BorshDeserialize };
+use crate::protocol::{ GameEvent, BoardSnapshot };
+
+/// Server events waiting to be applied. Events arrive numbered, and are handed out strictly
+/// in order and only once the board is done animating the previous one.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Default)]
+pub struct EventQueue {
+    pub last_seq: u64, // Sequence number of the last event or snapshot applied
+    pub pending: BTreeMap<u64, GameEvent>, // Events received ahead of their turn
+    pub snapshot: Option<(u64, BoardSnapshot)>, // A full board waiting to replace the current one
+    pub snapshot_requested: bool, // A gap was found and the server was asked for a snapshot
+}
+
+/// The next thing to apply to the board
+pub enum QueuedUpdate {
+    Event(GameEvent),
+    Snapshot(BoardSnapshot),
+}
+
+impl EventQueue {
+    /// Queues an event, dropping ones that were already applied or covered by a snapshot
+    pub fn push_event(&mut self, seq: u64, event: GameEvent) {
+        if seq > self.last_seq {
+            self.pending.insert(seq, event);
+        }
+    }
+
+    /// Queues a full board; only the newest snapshot is worth keeping
+    pub fn push_snapshot(&mut self, seq: u64, board: BoardSnapshot) {
+        let is_newer = self.snapshot.as_ref().is_none_or(|(queued, _)| seq >= *queued);
+        if is_newer {
+            self.snapshot = Some((seq, board));
+        }
+    }
+
+    /// Takes the next update in sequence. Events that directly follow the last one go first,
+    /// so their animations still play; a snapshot is used once the events run out or a gap
+    /// is reached, and replaces every event it covers.
+    pub fn pop(&mut self) -> Option<QueuedUpdate> {
+        let next_seq = self.last_seq + 1;
+        let snapshot_seq = self.snapshot.as_ref().map(|(seq, _)| *seq);
+        let event_is_next = self.pending.contains_key(&next_seq);
+        if event_is_next && snapshot_seq.is_none_or(|seq| next_seq <= seq) {
+            self.last_seq = next_seq;
+            return self.pending.remove(&next_seq).map(QueuedUpdate::Event);
+        }
+
+        let (seq, board) = self.snapshot.take()?;
+        if seq < self.last_seq {
+            // Older than what is already on screen
+            return self.pop();
+        }
+        self.last_seq = seq;
+        self.pending.retain(|event_seq, _| *event_seq > seq);
+        self.snapshot_requested = false;
+        Some(QueuedUpdate::Snapshot(board))
+    }
+
+    /// Returns true when nothing is waiting to be applied
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty() && self.snapshot.is_none()
+    }
+
+    /// Returns true when an event is waiting on one that never arrived and no snapshot is on
+    /// its way to fill the gap
+    pub fn has_gap(&self) -> bool {
+        if self.snapshot.is_some() || self.snapshot_requested {
+            return false;
+        }
+        self.pending
+            .keys()
+            .next()
+            .is_some_and(|seq| *seq > self.last_seq + 1)
+    }
+}
@@ -1,4 +1,5 @@
 use turbo::*;
+use std::collections::HashMap;
 use crate::game::cards::card::Card;
 
 use crate::game::animation::{
@@ -9,44 +10,99 @@ use crate::game::animation::{
     animate_tile_to_index,
 };
 
-use crate::GameState;
-use crate::game::map::clear_highlights;
+use crate::{ GameState, PlayerId };
+use crate::scene::{ Scene, GameMode };
+use crate::game::map::{ clear_highlights, BoardSize };
+use crate::game::map::board::unlock_door;
 use crate::game::map::fireball::Fireball;
-use crate::game::map::tile::Tile;
-use crate::game::constants::HAND_SIZE;
+use crate::game::map::tile::{ Tile, TileKind, Direction };
+use crate::game::animation::{ PendingFireballHit, highlight_selected_card_tiles };
+use crate::game::cards::card_effect::CardEffect;
+use crate::protocol::{
+    ClientToServer,
+    RejectReason,
+    RoomInfo,
+    JoinRejectReason,
+    IncompatibleReason,
+    BoardChange,
+    DailyResult,
+};
+use crate::protocol::board_sync::apply_tile_rotation;
+use crate::rules::{ Replay, MatchOutcome };
+use crate::game::constants::PROTOCOL_VERSION;
+
+pub fn receive_lobby_hello_accepted(game_state: &mut GameState, protocol_version: u32) {
+    log!("📨 [RECEIVE] Lobby accepted hello, protocol v{}", protocol_version);
+    game_state.lobby_greeted = true;
+}
+
+pub fn receive_hello_accepted(game_state: &mut GameState, protocol_version: u32) {
+    log!("📨 [RECEIVE] Room accepted hello, protocol v{}", protocol_version);
+    game_state.room_greeted = true;
+}
+
+/// The server can't talk to this build, anything else it sends would misdecode
+pub fn receive_incompatible(
+    game_state: &mut GameState,
+    protocol_version: u32,
+    reason: IncompatibleReason
+) {
+    log!(
+        "❌ [RECEIVE] Incompatible with server ({:?}): server speaks v{}, client speaks v{}",
+        reason,
+        protocol_version,
+        PROTOCOL_VERSION
+    );
+    game_state.incompatible = Some(reason);
+}
 
-pub fn receive_connected_users(game_state: &mut GameState, users: Vec<String>) {
-    log!("📨 [RECEIVE] Connected users: {:?}", users);
+pub fn receive_connected_users(
+    game_state: &mut GameState,
+    users: Vec<String>,
+    spectators: Vec<String>,
+    reconnecting: Vec<String>,
+    ready: Vec<String>
+) {
+    log!(
+        "📨 [RECEIVE] Connected users: {:?}, spectators: {:?}, reconnecting: {:?}, ready: {:?}",
+        users,
+        spectators,
+        reconnecting,
+        ready
+    );
 
     game_state.in_lobby = users.clone();
-    game_state.user_id_to_player_id.clear();
-
-    // Handle player mapping for any number of players
-    if !game_state.in_lobby.is_empty() {
-        log!("📨 [RECEIVE] Inserting player 1");
-        game_state.user_id_to_player_id.insert(
-            game_state.in_lobby[0].clone(),
-            crate::game::map::PlayerId::Player1
-        );
+    game_state.spectators = spectators;
+    game_state.reconnecting = reconnecting;
+    game_state.ready = ready;
+}
 
-        if game_state.in_lobby.len() >= 2 {
-            log!("📨 [RECEIVE] Inserting player 2");
-            game_state.user_id_to_player_id.insert(
-                game_state.in_lobby[1].clone(),
-                crate::game::map::PlayerId::Player2
-            );
-        }
-    }
+/// The server decides which baby each user controls, clients never derive it themselves
+pub fn receive_seat_assignment(game_state: &mut GameState, player_ids: HashMap<String, PlayerId>) {
+    log!("📨 [RECEIVE] Seat assignment: {:?}", player_ids);
+    game_state.user_id_to_player_id = player_ids;
+}
+
+/// The server started the match, or it was already running when this client arrived
+pub fn receive_start_game(game_state: &mut GameState) {
+    log!("📨 [RECEIVE] Start game");
+    game_state.scene = Scene::Game;
 }
 
 pub fn receive_board_state(
     game_state: &mut GameState,
+    seed: u64,
+    board_size: BoardSize,
     tiles: Vec<crate::game::map::Tile>,
     players: Vec<crate::game::map::Player>,
     monster: Option<crate::game::map::Monster>,
     current_turn: Option<crate::server::CurrentTurn>
 ) {
-    log!("📨 [RECEIVE] Board state, current_turn: {:?}", current_turn);
+    log!(
+        "📨 [RECEIVE] Board state {}, current_turn: {:?}",
+        game_state.events.last_seq,
+        current_turn
+    );
 
     // Check if the turn has changed to a different player
     let turn_changed = if
@@ -64,18 +120,79 @@ pub fn receive_board_state(
         game_state.reset_turn();
     }
 
+    // Keep local highlights within a turn; they come from the selected card, not the server
+    let highlighted: Vec<bool> = if turn_changed {
+        Vec::new()
+    } else {
+        game_state.tiles
+            .iter()
+            .map(|tile| tile.is_highlighted)
+            .collect()
+    };
+
     // Update game state
+    game_state.seed = seed;
+    game_state.board_size = board_size;
     game_state.tiles = tiles;
+    for (tile, is_highlighted) in game_state.tiles.iter_mut().zip(highlighted) {
+        tile.is_highlighted = is_highlighted;
+    }
     game_state.players = players;
     game_state.monster = monster;
     game_state.current_turn = current_turn.clone();
 }
 
+/// Applies the board changes of one sync
+pub fn receive_board_delta(game_state: &mut GameState, changes: Vec<BoardChange>) {
+    log!("📨 [RECEIVE] Board delta {}: {:?}", game_state.events.last_seq, changes);
+    for change in changes {
+        match change {
+            BoardChange::TileRotated { tile_index, current_rotation, original_rotation } => {
+                if let Some(tile) = game_state.tiles.get_mut(tile_index) {
+                    apply_tile_rotation(tile, current_rotation, original_rotation);
+                }
+            }
+            BoardChange::TileMoved { tile_index, mut tile } => {
+                if let Some(slot) = game_state.tiles.get_mut(tile_index) {
+                    tile.is_highlighted = slot.is_highlighted;
+                    *slot = tile;
+                }
+            }
+            BoardChange::PlayerHealthChanged { player_index, health } => {
+                if let Some(player) = game_state.players.get_mut(player_index) {
+                    player.health = health;
+                }
+            }
+            BoardChange::PlayerChanged { player_index, player } => {
+                if player_index < game_state.players.len() {
+                    game_state.players[player_index] = player;
+                } else {
+                    game_state.players.push(player);
+                }
+            }
+            BoardChange::MonsterChanged { monster } => {
+                game_state.monster = monster;
+            }
+            BoardChange::TurnChanged { current_turn } => {
+                log!("📨 [RECEIVE] Turn changed, starting new turn");
+                game_state.reset_turn();
+                clear_highlights(&mut game_state.tiles);
+                game_state.current_turn = current_turn;
+            }
+            BoardChange::SeedChanged { seed } => {
+                game_state.seed = seed;
+            }
+        }
+    }
+}
+
+/// `from_replay` is set while watching a replay, where even the local player's own actions
+/// were never applied on this board
 pub fn receive_card_cancelled(
     game_state: &mut GameState,
     card: &Card,
     player_id: &str,
-    board_tiles: &[Tile]
+    from_replay: bool
 ) {
     log!(
         "📨 [RECEIVE] Card cancelled by {}: {:?}, hand_index: {:?}",
@@ -84,19 +201,24 @@ pub fn receive_card_cancelled(
         card.hand_index
     );
 
-    if game_state.user == player_id {
+    if game_state.user == player_id && !from_replay {
+        // The local player already undid the card's effect when cancelling it
         game_state.selected_card = None;
+    } else if let CardEffect::RotateCard = card.effect {
+        CardEffect::revert_tile_rotations(&mut game_state.tiles);
+    } else if let CardEffect::SwapCard = card.effect {
+        CardEffect::revert_tile_positions(game_state);
     }
 
     clear_highlights(&mut game_state.tiles);
-    game_state.tiles = board_tiles.to_vec();
 }
 
 pub fn receive_tile_rotation(
     game_state: &mut GameState,
     tile_index: &usize,
     tile: &Tile,
-    player_id: &str
+    player_id: &str,
+    from_replay: bool
 ) {
     log!(
         "📨 [RECEIVE] Tile rotation: index={}, rotation={}, player={}",
@@ -104,7 +226,7 @@ pub fn receive_tile_rotation(
         tile.current_rotation,
         player_id
     );
-    let is_local_player = game_state.user == player_id;
+    let is_local_player = game_state.user == player_id && !from_replay;
     if !is_local_player {
         start_tile_rotation_animation(game_state, *tile_index, 0.25);
     }
@@ -114,7 +236,8 @@ pub fn receive_player_moved(
     game_state: &mut GameState,
     player_id: &str,
     new_position: &(usize, usize),
-    is_canceled: bool
+    is_canceled: bool,
+    from_replay: bool
 ) {
     log!(
         "📨 [RECEIVE] Player moved: player={}, new_position={:?}, is_canceled={}",
@@ -124,7 +247,7 @@ pub fn receive_player_moved(
     );
 
     // Only start animation for non-local players (local player animation is handled in card_effect)
-    let is_local_player = game_state.user == player_id;
+    let is_local_player = game_state.user == player_id && !from_replay;
     if is_local_player {
         log!("📨 [RECEIVE] Local player moved, skipping animation (already handled)");
         return;
@@ -171,6 +294,69 @@ pub fn receive_player_moved(
     }
 }
 
+pub fn receive_action_rejected(
+    game_state: &mut GameState,
+    reason: &RejectReason,
+    request: &ClientToServer
+) {
+    log!("📨 [RECEIVE] Action rejected: {:?}, reason: {:?}", request, reason);
+
+    // Undo the optimistic local state for the rejected request. The server follows up with
+    // a BoardState, so finished changes are restored from that; in-flight animations must be
+    // stopped here or they would reapply the change on top of it.
+    match request {
+        ClientToServer::MovePlayer { .. } => {
+            if let Some(anim) = game_state.animated_player.take() {
+                let player = game_state.get_player_by_user_id(&anim.player_id);
+                if let Some(player) = player {
+                    player.position = anim.origin_pos;
+                    player.set_moving(false);
+                }
+            }
+        }
+        ClientToServer::RotateTile { tile_index } => {
+            if let Some(tile) = game_state.tiles.get_mut(*tile_index) {
+                tile.rotation_anim = None;
+            }
+        }
+        ClientToServer::SwapTiles { .. } => {
+            game_state.swap_tiles_selected.clear();
+        }
+        ClientToServer::SelectCard { hand_index } => {
+            // Send the card back out of the play area
+            if
+                let Some(slot) = game_state.play_area
+                    .iter_mut()
+                    .find(|card| card.hand_index == Some(*hand_index))
+            {
+                *slot = Card::dummy_card();
+            }
+            game_state.selected_card = None;
+            clear_highlights(&mut game_state.tiles);
+        }
+        ClientToServer::ConfirmCard { hand_index } => {
+            restore_selected_card(game_state, |card| card.hand_index == Some(*hand_index));
+        }
+        ClientToServer::FireballShot { .. } => {
+            restore_selected_card(game_state, |card| card.effect == CardEffect::FireCard);
+        }
+        _ => {}
+    }
+}
+
+/// Makes a card that is still sitting in the play area the selected card again
+fn restore_selected_card(game_state: &mut GameState, matches: impl Fn(&Card) -> bool) {
+    if game_state.selected_card.is_some() {
+        return;
+    }
+    game_state.selected_card = game_state.play_area
+        .iter()
+        .rev()
+        .find(|card| matches(card))
+        .cloned();
+    highlight_selected_card_tiles(game_state);
+}
+
 pub fn receive_card_confirmed(game_state: &mut GameState, card: &Card, player_id: &str) {
     log!("📨 [RECEIVE] Card confirmed by {}: {:?}", player_id, card);
 
@@ -201,29 +387,40 @@ pub fn receive_fireball_shot(
     game_state: &mut GameState,
     player_id: &str,
     tile_index: &usize,
-    direction: &crate::game::map::tile::Direction
+    direction: &Direction,
+    end_tile_index: &usize,
+    hits_target: bool
 ) {
-    log!("📨 [RECEIVE] Fireball shot: index={}, direction={:?}", tile_index, direction);
-
-    let is_local_player = game_state.user == player_id;
-    if is_local_player {
-        return;
-    }
+    log!(
+        "📨 [RECEIVE] Fireball shot: index={}, direction={:?}, end={}, hits_target={}",
+        tile_index,
+        direction,
+        end_tile_index,
+        hits_target
+    );
 
-    let player = game_state.get_player_by_user_id(player_id).unwrap();
-    let position = player.position;
+    let shooter_id = match game_state.get_player_by_user_id(player_id) {
+        Some(player) => player.id.clone(),
+        None => {
+            log!("📨 [RECEIVE] Could not find PlayerId for user_id: {}", player_id);
+            return;
+        }
+    };
+    let position = game_state.board_size.position(*tile_index);
 
     // Create fireball
-    let fireball = Fireball::new(
-        10,
-        Tile::position(*tile_index),
-        direction.clone(),
-        player.id.clone()
-    );
+    let fireball = Fireball::new(10, position, *direction, shooter_id);
     let fireball_id = fireball.id;
     game_state.fireballs.push(fireball);
 
-    start_fireball_animation(game_state, fireball_id, position, direction.clone(), *tile_index);
+    start_fireball_animation(
+        game_state,
+        fireball_id,
+        position,
+        *direction,
+        *end_tile_index,
+        hits_target
+    );
 }
 
 pub fn receive_fireball_hit_result(
@@ -241,6 +438,29 @@ pub fn receive_fireball_hit_result(
         monster_damage
     );
 
+    // Wait for the fireball to reach its target before showing the damage
+    if !game_state.animated_fireballs.is_empty() {
+        game_state.pending_fireball_hit = Some(PendingFireballHit {
+            player_id: player_id.to_string(),
+            target_id: target_id.to_string(),
+            damage_dealt: *damage_dealt,
+            monster_damage,
+        });
+        return;
+    }
+
+    apply_fireball_hit_result(game_state, player_id, target_id, damage_dealt, monster_damage);
+}
+
+pub fn apply_fireball_hit_result(
+    game_state: &mut GameState,
+    player_id: &str,
+    target_id: &str,
+    damage_dealt: &u32,
+    monster_damage: Option<u32>
+) {
+    log!("📨 [RECEIVE] Applying fireball hit from {} on {}", player_id, target_id);
+
     // Handle monster damage if present
     if let Some(damage) = monster_damage {
         if let Some(monster) = &mut game_state.monster {
@@ -270,36 +490,172 @@ pub fn receive_player_damage_from_monster(
     }
 }
 
-pub fn receive_game_over(game_state: &mut GameState, winner_ids: &[String], loser_ids: &[String]) {
+pub fn receive_key_picked_up(game_state: &mut GameState, player_id: &str, tile_index: usize) {
+    log!("📨 [RECEIVE] Player {} picked up the key on tile {}", player_id, tile_index);
+    if let Some(tile) = game_state.tiles.get_mut(tile_index) {
+        tile.has_key = false;
+    }
+    if let Some(player) = game_state.get_player_by_user_id(player_id) {
+        player.keys += 1;
+    }
+}
+
+pub fn receive_door_unlocked(
+    game_state: &mut GameState,
+    player_id: &str,
+    tile_index: usize,
+    direction: Direction
+) {
+    log!(
+        "📨 [RECEIVE] Player {} unlocked the door {:?} of tile {}",
+        player_id,
+        direction,
+        tile_index
+    );
+    let size = game_state.board_size;
+    if tile_index < game_state.tiles.len() {
+        unlock_door(&mut game_state.tiles, size, tile_index, direction);
+    }
+    if let Some(player) = game_state.get_player_by_user_id(player_id) {
+        player.keys = player.keys.saturating_sub(1);
+    }
+}
+
+pub fn receive_player_burned(game_state: &mut GameState, player_id: &str, damage_dealt: u32) {
+    log!("📨 [RECEIVE] Player {} took {} damage from lava", player_id, damage_dealt);
+    if let Some(player) = game_state.get_player_by_user_id(player_id) {
+        player.take_damage(damage_dealt);
+    }
+}
+
+pub fn receive_player_healed(game_state: &mut GameState, player_id: &str, amount: u32) {
+    log!("📨 [RECEIVE] Player {} healed {} at a fountain", player_id, amount);
+    if let Some(player) = game_state.get_player_by_user_id(player_id) {
+        player.heal(amount);
+    }
+}
+
+pub fn receive_player_teleported(
+    game_state: &mut GameState,
+    player_id: &str,
+    new_position: (usize, usize)
+) {
+    log!("📨 [RECEIVE] Player {} came out of a portal at {:?}", player_id, new_position);
+    if let Some(player) = game_state.get_player_by_user_id(player_id) {
+        player.position = new_position;
+    }
+    // The player is somewhere else now, so are the tiles they can reach
+    if game_state.user == player_id {
+        clear_highlights(&mut game_state.tiles);
+    }
+}
+
+pub fn receive_treasure_found(
+    game_state: &mut GameState,
+    player_id: &str,
+    tile_index: usize,
+    card: Option<Card>
+) {
+    log!("📨 [RECEIVE] Player {} looted the treasure room on tile {}", player_id, tile_index);
+    if let Some(tile) = game_state.tiles.get_mut(tile_index) {
+        tile.kind = TileKind::Corridor;
+    }
+    // Only the finder is sent the card, like the rest of their hand
+    let card = match card {
+        Some(card) => card,
+        None => {
+            return;
+        }
+    };
+    if let Some(player) = game_state.get_player_by_user_id(player_id) {
+        let slot = card.hand_index.and_then(|hand_index| player.hand.get_mut(hand_index));
+        if let Some(slot) = slot {
+            *slot = card;
+        }
+    }
+}
+
+pub fn receive_game_over(
+    game_state: &mut GameState,
+    winner_ids: &[String],
+    loser_ids: &[String],
+    outcome: MatchOutcome
+) {
     if winner_ids.len() > 1 && loser_ids.is_empty() {
-        log!("🏆 [RECEIVE] Game Over! Both players win: {:?}", winner_ids);
-        // Both players defeated the monster together
-        game_state.game_over_cooperative(winner_ids, &[]);
+        log!("🏆 [RECEIVE] Game Over! All players win ({:?}): {:?}", outcome, winner_ids);
+        // The players defeated the monster or escaped together
+        game_state.game_over_cooperative(winner_ids, &[], outcome);
     } else if winner_ids.is_empty() && loser_ids.len() > 1 {
-        log!("💀 [RECEIVE] Game Over! Both players lose: {:?}", loser_ids);
-        // Both players were defeated by the monster
-        game_state.game_over_cooperative(&[], loser_ids);
+        log!("💀 [RECEIVE] Game Over! All players lose: {:?}", loser_ids);
+        // The players were defeated by the monster
+        game_state.game_over_cooperative(&[], loser_ids, outcome);
     } else {
         log!(
             "❓ [RECEIVE] Game Over! Unexpected state - winners: {:?}, losers: {:?}",
             winner_ids,
             loser_ids
         );
-        game_state.game_over_cooperative(&[], &["Error".to_string()]);
+        game_state.game_over_cooperative(&[], &["Error".to_string()], outcome);
     }
 }
 
 pub fn receive_reset_game(game_state: &mut GameState) {
     log!("🔄 [RECEIVE] Game reset received");
-    game_state.animated_card = None;
-    game_state.animated_player = None;
-    game_state.animated_tiles.clear();
-    game_state.animated_fireballs.clear();
-    game_state.fireballs.clear();
-    game_state.selected_card = None;
-    game_state.swap_tiles_selected.clear();
-    game_state.pending_swaps.clear();
-    game_state.reset_turn();
-    game_state.play_area.clear();
-    game_state.play_area = vec![Card::dummy_card(); HAND_SIZE];
+    game_state.reset_match();
+    // A reset from any seat starts the next match, including for players on the game over
+    // screen
+    if matches!(game_state.scene, Scene::GameOver { .. }) {
+        game_state.scene = Scene::Game;
+    }
+}
+
+pub fn receive_room_list(game_state: &mut GameState, rooms: Vec<RoomInfo>) {
+    log!("📨 [RECEIVE] Room list: {:?}", rooms);
+    game_state.rooms = rooms;
+}
+
+pub fn receive_room_joined(
+    game_state: &mut GameState,
+    room_id: String,
+    is_private: bool,
+    player_count: usize,
+    mode: GameMode,
+    board_size: BoardSize
+) {
+    log!(
+        "📨 [RECEIVE] Joined {:?} room: {} (private: {}, players: {}, board: {})",
+        mode,
+        room_id,
+        is_private,
+        player_count,
+        board_size
+    );
+    game_state.enter_room(room_id, is_private, player_count, mode, board_size);
+}
+
+pub fn receive_replay(game_state: &mut GameState, replay: Replay) {
+    log!(
+        "📨 [RECEIVE] Replay of seed {} with {} steps",
+        replay.seed(),
+        replay.steps.len()
+    );
+    // The replay returns to the game over screen it was asked for from
+    game_state.reset_match();
+    game_state.start_replay(replay);
+}
+
+pub fn receive_daily_results(game_state: &mut GameState, day: u64, results: Vec<DailyResult>) {
+    log!("📨 [RECEIVE] Daily results for day {}: {:?}", day, results);
+    game_state.scene = Scene::DailyResults { day, results };
+}
+
+pub fn receive_join_rejected(
+    game_state: &mut GameState,
+    room_id: &str,
+    reason: &JoinRejectReason
+) {
+    log!("📨 [RECEIVE] Could not join room {}: {:?}", room_id, reason);
+    // Drop the stale entry until the lobby sends a fresh list
+    game_state.rooms.retain(|room| room.room_id != room_id);
+    game_state.join_error = Some(*reason);
 }
@@ -1,88 +1,205 @@
 use turbo::*;
-use crate::game::cards::card::Card;
-use crate::server::GameChannel;
-use crate::game::constants::GAME_CHANNEL;
-use super::ClientToServer;
+use crate::server::{ GameChannel, LobbyChannel };
+use crate::game::constants::{ LOBBY_CHANNEL, PROTOCOL_VERSION, CLIENT_BUILD };
+use crate::protocol::{ ClientToServer, ClientToLobby };
+use crate::scene::GameMode;
+use crate::game::map::BoardSize;
 
-pub fn send_reset_game() {
-    log!("🚀 [SEND] Reset game");
-    let msg = ClientToServer::ResetGame;
-    if let Some(conn) = GameChannel::subscribe(GAME_CHANNEL) {
+pub fn send_hello(room_id: &str) {
+    log!("🚀 [SEND] Hello, protocol v{} build {}", PROTOCOL_VERSION, CLIENT_BUILD);
+    let msg = ClientToServer::Hello {
+        protocol_version: PROTOCOL_VERSION,
+        client_build: CLIENT_BUILD.to_string(),
+    };
+    if let Some(conn) = GameChannel::subscribe(room_id) {
+        let _ = conn.send(&msg);
+    }
+}
+
+pub fn send_request_snapshot(room_id: &str) {
+    log!("🚀 [SEND] Request board snapshot");
+    let msg = ClientToServer::RequestSnapshot;
+    if let Some(conn) = GameChannel::subscribe(room_id) {
+        let _ = conn.send(&msg);
+    }
+}
+
+pub fn send_request_replay(room_id: &str) {
+    log!("🚀 [SEND] Request replay");
+    let msg = ClientToServer::RequestReplay;
+    if let Some(conn) = GameChannel::subscribe(room_id) {
         let _ = conn.send(&msg);
     }
 }
 
-pub fn send_card_selection(hand_index: usize) {
+pub fn send_reset_game(room_id: &str, seed: Option<u64>) {
+    log!("🚀 [SEND] Reset game, seed: {:?}", seed);
+    let msg = ClientToServer::ResetGame { seed };
+    if let Some(conn) = GameChannel::subscribe(room_id) {
+        let _ = conn.send(&msg);
+    }
+}
+
+pub fn send_card_selection(room_id: &str, hand_index: usize) {
     log!("🚀 [SEND] Card selection at hand index: {}", hand_index);
     let msg = ClientToServer::SelectCard { hand_index };
-    if let Some(conn) = GameChannel::subscribe(GAME_CHANNEL) {
+    if let Some(conn) = GameChannel::subscribe(room_id) {
         let _ = conn.send(&msg);
     }
 }
 
-pub fn send_card_cancel(hand_index: usize) {
+pub fn send_card_cancel(room_id: &str, hand_index: usize) {
     log!("🚀 [SEND] Card cancel at hand index: {}", hand_index);
     let msg = ClientToServer::CancelSelectCard { hand_index };
-    if let Some(conn) = GameChannel::subscribe(GAME_CHANNEL) {
+    if let Some(conn) = GameChannel::subscribe(room_id) {
         let _ = conn.send(&msg);
     }
 }
 
-pub fn send_end_turn() {
+pub fn send_end_turn(room_id: &str) {
     log!("🚀 [SEND] End turn");
     let msg = ClientToServer::EndTurn;
-    if let Some(conn) = GameChannel::subscribe(GAME_CHANNEL) {
+    if let Some(conn) = GameChannel::subscribe(room_id) {
         let _ = conn.send(&msg);
     }
 }
 
-pub fn send_tile_rotation(tile_index: usize) {
+pub fn send_tile_rotation(room_id: &str, tile_index: usize) {
     log!("🚀 [SEND] Rotate tile index: {}", tile_index);
     let msg = ClientToServer::RotateTile { tile_index };
-    if let Some(conn) = GameChannel::subscribe(GAME_CHANNEL) {
+    if let Some(conn) = GameChannel::subscribe(room_id) {
         let _ = conn.send(&msg);
     }
 }
 
-pub fn send_move(new_position: (usize, usize), is_canceled: bool) {
+pub fn send_move(room_id: &str, new_position: (usize, usize), is_canceled: bool) {
     log!("🚀 [SEND] Player move to: {:?} (canceled: {})", new_position, is_canceled);
     let msg = ClientToServer::MovePlayer { new_position, is_canceled };
-    if let Some(conn) = GameChannel::subscribe(GAME_CHANNEL) {
+    if let Some(conn) = GameChannel::subscribe(room_id) {
         let _ = conn.send(&msg);
     }
 }
 
-pub fn send_confirm_card(card: &Card) {
-    log!("🚀 [SEND] Card confirmed: {}", card.name);
-    let msg = ClientToServer::ConfirmCard { card: card.clone() };
-    if let Some(conn) = GameChannel::subscribe(GAME_CHANNEL) {
+pub fn send_confirm_card(room_id: &str, hand_index: usize) {
+    log!("🚀 [SEND] Card confirmed at hand index: {}", hand_index);
+    let msg = ClientToServer::ConfirmCard { hand_index };
+    if let Some(conn) = GameChannel::subscribe(room_id) {
         let _ = conn.send(&msg);
     }
 }
 
-pub fn send_swap_tiles(tile_index_1: usize, tile_index_2: usize) {
+pub fn send_swap_tiles(room_id: &str, tile_index_1: usize, tile_index_2: usize) {
     log!("🚀 [SEND] Swap tiles: {} <-> {}", tile_index_1, tile_index_2);
     let msg = ClientToServer::SwapTiles { tile_index_1, tile_index_2 };
-    if let Some(conn) = GameChannel::subscribe(GAME_CHANNEL) {
+    if let Some(conn) = GameChannel::subscribe(room_id) {
         let _ = conn.send(&msg);
     }
 }
 
-pub fn send_fireball_shot(target_tile: usize, direction: crate::game::map::tile::Direction) {
+pub fn send_fireball_shot(
+    room_id: &str,
+    target_tile: usize,
+    direction: crate::game::map::tile::Direction
+) {
     log!("🚀 [SEND] Fireball shot at tile {} in direction {:?}", target_tile, direction);
     let msg = ClientToServer::FireballShot { target_tile, direction };
-    if let Some(conn) = GameChannel::subscribe(GAME_CHANNEL) {
+    if let Some(conn) = GameChannel::subscribe(room_id) {
+        let _ = conn.send(&msg);
+    }
+}
+
+pub fn send_set_ready(room_id: &str, ready: bool) {
+    log!("🚀 [SEND] Set ready: {}", ready);
+    let msg = ClientToServer::SetReady { ready };
+    if let Some(conn) = GameChannel::subscribe(room_id) {
         let _ = conn.send(&msg);
     }
 }
 
-pub fn send_fireball_hit(shooter_id: String, hit_tile_index: usize) {
-    log!("🚀 [SEND] Fireball hit: shooter={}, hit_position={:?}", shooter_id, hit_tile_index);
-    let msg = ClientToServer::FireballHit {
-        shooter_id,
-        hit_tile_index,
+pub fn send_take_seat(room_id: &str) {
+    log!("🚀 [SEND] Take seat");
+    let msg = ClientToServer::TakeSeat;
+    if let Some(conn) = GameChannel::subscribe(room_id) {
+        let _ = conn.send(&msg);
+    }
+}
+
+pub fn send_configure_match(
+    room_id: &str,
+    player_count: usize,
+    mode: GameMode,
+    board_size: BoardSize
+) {
+    log!(
+        "🚀 [SEND] Configure {:?} match for {} players on a {} board",
+        mode,
+        player_count,
+        board_size
+    );
+    let msg = ClientToServer::ConfigureMatch { player_count, mode, board_size };
+    if let Some(conn) = GameChannel::subscribe(room_id) {
+        let _ = conn.send(&msg);
+    }
+}
+
+pub fn send_lobby_hello() {
+    log!("🚀 [SEND] Lobby hello, protocol v{} build {}", PROTOCOL_VERSION, CLIENT_BUILD);
+    let msg = ClientToLobby::Hello {
+        protocol_version: PROTOCOL_VERSION,
+        client_build: CLIENT_BUILD.to_string(),
     };
-    if let Some(conn) = GameChannel::subscribe(GAME_CHANNEL) {
+    if let Some(conn) = LobbyChannel::subscribe(LOBBY_CHANNEL) {
+        let _ = conn.send(&msg);
+    }
+}
+
+pub fn send_create_room(
+    is_private: bool,
+    player_count: usize,
+    mode: GameMode,
+    board_size: BoardSize
+) {
+    log!(
+        "🚀 [SEND] Create {:?} room (private: {}, players: {}, board: {})",
+        mode,
+        is_private,
+        player_count,
+        board_size
+    );
+    let msg = ClientToLobby::Create { is_private, player_count, mode, board_size };
+    if let Some(conn) = LobbyChannel::subscribe(LOBBY_CHANNEL) {
+        let _ = conn.send(&msg);
+    }
+}
+
+pub fn send_join_room(room_id: &str) {
+    log!("🚀 [SEND] Join room: {}", room_id);
+    let msg = ClientToLobby::Join { room_id: room_id.to_string() };
+    if let Some(conn) = LobbyChannel::subscribe(LOBBY_CHANNEL) {
+        let _ = conn.send(&msg);
+    }
+}
+
+pub fn send_watch_room(room_id: &str) {
+    log!("🚀 [SEND] Watch room: {}", room_id);
+    let msg = ClientToLobby::Watch { room_id: room_id.to_string() };
+    if let Some(conn) = LobbyChannel::subscribe(LOBBY_CHANNEL) {
+        let _ = conn.send(&msg);
+    }
+}
+
+pub fn send_request_daily_results() {
+    log!("🚀 [SEND] Request daily results");
+    let msg = ClientToLobby::RequestDailyResults;
+    if let Some(conn) = LobbyChannel::subscribe(LOBBY_CHANNEL) {
+        let _ = conn.send(&msg);
+    }
+}
+
+pub fn send_leave_room() {
+    log!("🚀 [SEND] Leave room");
+    let msg = ClientToLobby::Leave;
+    if let Some(conn) = LobbyChannel::subscribe(LOBBY_CHANNEL) {
         let _ = conn.send(&msg);
     }
 }
@@ -0,0 +1,188 @@
+// Turn-by-turn board sync: the server diffs the board against the last one it sent and
+// broadcasts only what changed. Every change carries the resulting value rather than an
+// operation, so applying one twice, or on top of the live events of the same turn, is harmless.
+use crate::game::map::{ Tile, Player, Monster, BoardSize };
+use crate::server::CurrentTurn;
+use crate::rules::Match;
+use serde::{ Serialize, Deserialize };
+use borsh::{ BorshSerialize, BorshDeserialize };
+
+/// The board as it was last sent to the clients
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BoardSnapshot {
+    pub seed: u64, // What the match was dealt from, shown so it can be played again
+    pub board_size: BoardSize, // Only changes with a new match, which is always sent in full
+    pub tiles: Vec<Tile>,
+    pub players: Vec<Player>,
+    pub monster: Option<Monster>,
+    pub current_turn: Option<CurrentTurn>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub enum BoardChange {
+    TileRotated {
+        tile_index: usize,
+        current_rotation: u8,
+        original_rotation: u8,
+    },
+    TileMoved {
+        tile_index: usize,
+        tile: Tile, // The tile that now sits at this index
+    },
+    PlayerHealthChanged {
+        player_index: usize,
+        health: u32,
+    },
+    PlayerChanged {
+        player_index: usize,
+        player: Player, // Position, hand or anything beyond health changed
+    },
+    MonsterChanged {
+        monster: Option<Monster>,
+    },
+    TurnChanged {
+        current_turn: Option<CurrentTurn>,
+    },
+    SeedChanged {
+        seed: u64,
+    },
+}
+
+impl BoardSnapshot {
+    /// The board of a match, with the turn showing the user who plays it
+    pub fn of_match(game: &Match, seed: u64, players: &[String]) -> BoardSnapshot {
+        let current_turn = game.turn.as_ref().and_then(|turn| {
+            Some(CurrentTurn {
+                player_id: players.get(turn.player_index)?.clone(),
+                selected_card: turn.selected_card.clone(),
+                selected_card_index: turn.selected_card_index,
+            })
+        });
+        BoardSnapshot {
+            seed,
+            board_size: game.board_size,
+            tiles: game.tiles.clone(),
+            players: game.players.clone(),
+            monster: game.monster.clone(),
+            current_turn,
+        }
+    }
+
+    /// The board as one viewer may see it: only the viewer's own seat keeps its hand
+    pub fn for_viewer(&self, viewer_index: Option<usize>) -> BoardSnapshot {
+        BoardSnapshot {
+            players: self.players
+                .iter()
+                .enumerate()
+                .map(|(index, player)| {
+                    if Some(index) == viewer_index { player.clone() } else { player.without_hand() }
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Lists the changes that turn `prev` into `next`
+pub fn diff_board(prev: &BoardSnapshot, next: &BoardSnapshot) -> Vec<BoardChange> {
+    let mut changes = Vec::new();
+
+    for (tile_index, tile) in next.tiles.iter().enumerate() {
+        match prev.tiles.get(tile_index) {
+            Some(old) if same_tile(old, tile) => {}
+            Some(old) if same_tile(&rotated_like(old, tile), tile) => {
+                changes.push(BoardChange::TileRotated {
+                    tile_index,
+                    current_rotation: tile.current_rotation,
+                    original_rotation: tile.original_rotation,
+                });
+            }
+            _ => {
+                changes.push(BoardChange::TileMoved { tile_index, tile: tile.clone() });
+            }
+        }
+    }
+
+    for (player_index, player) in next.players.iter().enumerate() {
+        match prev.players.get(player_index) {
+            Some(old) if old == player => {}
+            Some(old) if (Player { health: player.health, ..old.clone() }) == *player => {
+                changes.push(BoardChange::PlayerHealthChanged {
+                    player_index,
+                    health: player.health,
+                });
+            }
+            _ => {
+                changes.push(BoardChange::PlayerChanged { player_index, player: player.clone() });
+            }
+        }
+    }
+
+    if prev.monster != next.monster {
+        changes.push(BoardChange::MonsterChanged { monster: next.monster.clone() });
+    }
+
+    let turn_changed =
+        prev.current_turn.as_ref().map(|turn| &turn.player_id) !=
+        next.current_turn.as_ref().map(|turn| &turn.player_id);
+    if turn_changed {
+        changes.push(BoardChange::TurnChanged { current_turn: next.current_turn.clone() });
+    }
+
+    if prev.seed != next.seed {
+        changes.push(BoardChange::SeedChanged { seed: next.seed });
+    }
+
+    changes
+}
+
+/// The changes as one viewer may see them: other players' hands are left out
+pub fn changes_for_viewer(
+    changes: &[BoardChange],
+    viewer_index: Option<usize>
+) -> Vec<BoardChange> {
+    changes
+        .iter()
+        .map(|change| {
+            match change {
+                BoardChange::PlayerChanged { player_index, player } if
+                    Some(*player_index) != viewer_index
+                => {
+                    BoardChange::PlayerChanged {
+                        player_index: *player_index,
+                        player: player.without_hand(),
+                    }
+                }
+                _ => change.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Applies a rotation change to a tile, keeping its walls in line with the new rotation
+pub fn apply_tile_rotation(tile: &mut Tile, current_rotation: u8, original_rotation: u8) {
+    tile.rotate_entrances(current_rotation);
+    tile.original_rotation = original_rotation;
+    tile.target_rotation = current_rotation;
+}
+
+/// `old` turned to the rotation of `new`, to tell a rotation apart from a different tile
+fn rotated_like(old: &Tile, new: &Tile) -> Tile {
+    let mut tile = old.clone();
+    apply_tile_rotation(&mut tile, new.current_rotation, new.original_rotation);
+    tile.target_rotation = new.target_rotation;
+    tile
+}
+
+/// Compares the synced parts of two tiles; highlights and animations are local to each client
+fn same_tile(a: &Tile, b: &Tile) -> bool {
+    a.entrances == b.entrances &&
+        a.kind == b.kind &&
+        a.is_exit == b.is_exit &&
+        a.locked == b.locked &&
+        a.has_key == b.has_key &&
+        a.original_location == b.original_location &&
+        a.original_rotation == b.original_rotation &&
+        a.current_rotation == b.current_rotation &&
+        a.target_rotation == b.target_rotation
+}
@@ -0,0 +1,441 @@
+// Messages exchanged between clients and the server channels, defined once for both sides.
+// Everything here is borsh encoded, so any change to these types must bump PROTOCOL_VERSION.
+use std::collections::HashMap;
+use crate::game::map::{ Tile, Player, PlayerId, Monster, BoardSize };
+use crate::game::map::tile::Direction;
+use crate::game::cards::card::Card;
+use crate::server::CurrentTurn;
+use crate::scene::GameMode;
+use crate::rules::{ MatchEvent, MatchOutcome, FireballTarget, Replay };
+use crate::game::constants::PROTOCOL_VERSION;
+use serde::{ Serialize, Deserialize };
+use borsh::{ BorshSerialize, BorshDeserialize };
+
+pub mod board_sync;
+pub use board_sync::{ BoardChange, BoardSnapshot };
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub enum ClientToServer {
+    // Sent on subscribe. Must stay the first variant so it decodes the same in every version
+    Hello {
+        protocol_version: u32,
+        client_build: String,
+    },
+    ResetGame {
+        seed: Option<u64>, // Deals the board of an earlier match, None for a new one
+    },
+    ConfigureMatch {
+        player_count: usize, // Sent on joining a room, ignored once the match has started
+        mode: GameMode,
+        board_size: BoardSize,
+    },
+    TakeSeat, // A spectator asks for the seat of a player who left
+    RequestSnapshot, // Sent when the client missed an event
+    SetReady {
+        ready: bool, // Toggled in the room lobby, the match starts once every seat is ready
+    },
+    RequestReplay, // Asks for the replay of the room's last match
+    EndTurn,
+    SelectCard {
+        hand_index: usize,
+    },
+    CancelSelectCard {
+        hand_index: usize,
+    },
+    ConfirmCard {
+        hand_index: usize,
+    },
+    RotateTile {
+        tile_index: usize,
+    },
+    MovePlayer {
+        new_position: (usize, usize),
+        is_canceled: bool,
+    },
+    SwapTiles {
+        tile_index_1: usize,
+        tile_index_2: usize,
+    },
+    FireballShot {
+        target_tile: usize,
+        direction: Direction,
+    },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub enum ClientToLobby {
+    // Sent on subscribe. Must stay the first variant so it decodes the same in every version
+    Hello {
+        protocol_version: u32,
+        client_build: String,
+    },
+    Create {
+        is_private: bool, // Private rooms are left out of the room list and joined by code
+        player_count: usize,
+        mode: GameMode,
+        board_size: BoardSize, // Daily rooms always play on the default size
+    },
+    Join {
+        room_id: String,
+    },
+    Watch {
+        room_id: String,
+    },
+    Leave,
+    RequestDailyResults, // Today's results, for the daily results screen
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub enum ServerToClient {
+    // Answers to Hello. Must stay the first variants so they decode the same in every version
+    HelloAccepted {
+        protocol_version: u32,
+    },
+    Incompatible {
+        protocol_version: u32, // The server's version
+        reason: IncompatibleReason,
+    },
+    ConnectedUsers {
+        users: Vec<String>, // One entry per seat, empty for a seat nobody holds
+        spectators: Vec<String>,
+        reconnecting: Vec<String>, // Seated users who dropped and still have their seat held
+        ready: Vec<String>, // Seated users ready to start the match
+    },
+    SeatAssignment {
+        player_ids: HashMap<String, PlayerId>, // Which baby each seated user controls
+    },
+    StartGame,
+    BoardState {
+        seq: u64, // The board right after the event with this sequence number
+        seed: u64,
+        board_size: BoardSize,
+        tiles: Vec<Tile>,
+        players: Vec<Player>,
+        monster: Option<Monster>,
+        current_turn: Option<CurrentTurn>,
+    },
+    Event {
+        seq: u64, // One more than the event before it, shared by every client in the room
+        event: GameEvent,
+    },
+    ActionRejected {
+        reason: RejectReason,
+        request: ClientToServer, // The request that was rejected, so the client can undo it
+    },
+    Replay {
+        replay: Replay,
+    },
+}
+
+/// Something that happened in the match. Clients apply events strictly in sequence order,
+/// one animation at a time; the other server messages are about the session and apply at once.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub enum GameEvent {
+    ResetGame,
+    BoardDelta {
+        changes: Vec<BoardChange>,
+    },
+    CardCancelled {
+        player_id: String,
+        card: Card,
+    },
+    CardConfirmed {
+        player_id: String,
+        card: Card,
+    },
+    TileRotated {
+        player_id: String,
+        tile_index: usize,
+        tile: Tile,
+    },
+    PlayerMoved {
+        player_id: String,
+        new_position: (usize, usize),
+        is_canceled: bool,
+    },
+    TilesSwapped {
+        tile_index_1: usize,
+        tile_index_2: usize,
+    },
+    FireballShot {
+        player_id: String,
+        tile_index: usize, // Tile the fireball starts from
+        direction: Direction,
+        end_tile_index: usize, // Tile the fireball stops on, decided by the server
+        hits_target: bool, // Whether it stops on a player/monster rather than a wall
+    },
+    FireballHit {
+        player_id: String,
+        target_id: String,
+        damage_dealt: u32,
+        monster_damage: Option<u32>, // Optional monster damage if fireball hit monster
+    },
+    PlayerDamageFromMonster {
+        player_id: String,
+        damage_dealt: u32,
+    },
+    KeyPickedUp {
+        player_id: String,
+        tile_index: usize,
+    },
+    DoorUnlocked {
+        player_id: String,
+        tile_index: usize, // Tile the player opened the door from
+        direction: Direction,
+    },
+    PlayerBurned {
+        player_id: String,
+        damage_dealt: u32,
+    },
+    PlayerHealed {
+        player_id: String,
+        amount: u32,
+    },
+    PlayerTeleported {
+        player_id: String,
+        new_position: (usize, usize), // Where the other portal of the pair is
+    },
+    TreasureFound {
+        player_id: String,
+        tile_index: usize,
+        card: Option<Card>, // Only for the finder, in the hand slot named by its hand_index
+    },
+    GameOver {
+        winner_ids: Vec<String>,
+        loser_ids: Vec<String>,
+        outcome: MatchOutcome, // Which condition ended the match
+    },
+}
+
+impl GameEvent {
+    /// What clients are told about something that happened in a match, with seats turned
+    /// into the users playing them. A new turn has no event of its own, it is sent as a
+    /// board sync.
+    pub fn from_match_event(event: MatchEvent, players: &[String]) -> Option<GameEvent> {
+        let seat_user = |player_index: usize| {
+            players.get(player_index).cloned().unwrap_or_default()
+        };
+        let game_event = match event {
+            MatchEvent::TurnStarted { .. } => {
+                return None;
+            }
+            MatchEvent::CardCancelled { player_index, card } => {
+                GameEvent::CardCancelled { player_id: seat_user(player_index), card }
+            }
+            MatchEvent::CardConfirmed { player_index, card } => {
+                GameEvent::CardConfirmed { player_id: seat_user(player_index), card }
+            }
+            MatchEvent::TileRotated { player_index, tile_index, tile } => {
+                GameEvent::TileRotated { player_id: seat_user(player_index), tile_index, tile }
+            }
+            MatchEvent::PlayerMoved { player_index, new_position, is_canceled } => {
+                GameEvent::PlayerMoved {
+                    player_id: seat_user(player_index),
+                    new_position,
+                    is_canceled,
+                }
+            }
+            MatchEvent::TilesSwapped { tile_index_1, tile_index_2 } => {
+                GameEvent::TilesSwapped { tile_index_1, tile_index_2 }
+            }
+            MatchEvent::FireballShot {
+                player_index,
+                tile_index,
+                direction,
+                end_tile_index,
+                hits_target,
+            } => {
+                GameEvent::FireballShot {
+                    player_id: seat_user(player_index),
+                    tile_index,
+                    direction,
+                    end_tile_index,
+                    hits_target,
+                }
+            }
+            MatchEvent::FireballHit { player_index, target, damage } => {
+                let (target_id, damage_dealt, monster_damage) = match target {
+                    FireballTarget::Monster => ("monster".to_string(), 0, Some(damage)),
+                    FireballTarget::Player(target_index) => (seat_user(target_index), damage, None),
+                };
+                GameEvent::FireballHit {
+                    player_id: seat_user(player_index),
+                    target_id,
+                    damage_dealt,
+                    monster_damage,
+                }
+            }
+            MatchEvent::MonsterAttacked { player_index, damage } => {
+                GameEvent::PlayerDamageFromMonster {
+                    player_id: seat_user(player_index),
+                    damage_dealt: damage,
+                }
+            }
+            MatchEvent::KeyPickedUp { player_index, tile_index } => {
+                GameEvent::KeyPickedUp { player_id: seat_user(player_index), tile_index }
+            }
+            MatchEvent::DoorUnlocked { player_index, tile_index, direction } => {
+                GameEvent::DoorUnlocked {
+                    player_id: seat_user(player_index),
+                    tile_index,
+                    direction,
+                }
+            }
+            MatchEvent::PlayerBurned { player_index, damage } => {
+                GameEvent::PlayerBurned { player_id: seat_user(player_index), damage_dealt: damage }
+            }
+            MatchEvent::PlayerHealed { player_index, amount } => {
+                GameEvent::PlayerHealed { player_id: seat_user(player_index), amount }
+            }
+            MatchEvent::PlayerTeleported { player_index, new_position } => {
+                GameEvent::PlayerTeleported { player_id: seat_user(player_index), new_position }
+            }
+            MatchEvent::TreasureFound { player_index, tile_index, card } => {
+                GameEvent::TreasureFound {
+                    player_id: seat_user(player_index),
+                    tile_index,
+                    card: Some(card),
+                }
+            }
+            MatchEvent::MatchEnded { outcome } => {
+                // The match is cooperative, every seat wins or loses together
+                let (winner_ids, loser_ids) = if outcome.players_won() {
+                    (players.to_vec(), Vec::new())
+                } else {
+                    (Vec::new(), players.to_vec())
+                };
+                GameEvent::GameOver { winner_ids, loser_ids, outcome }
+            }
+        };
+        Some(game_event)
+    }
+
+    /// Returns true if the event holds cards from a hand, which only their owner may see
+    pub fn is_private(&self) -> bool {
+        matches!(self, GameEvent::TreasureFound { card: Some(_), .. })
+    }
+
+    /// The event as one viewer may see it: other players' cards are left out
+    pub fn for_viewer(&self, viewer_id: &str) -> GameEvent {
+        match self {
+            GameEvent::TreasureFound { player_id, tile_index, .. } if player_id != viewer_id => {
+                GameEvent::TreasureFound {
+                    player_id: player_id.clone(),
+                    tile_index: *tile_index,
+                    card: None,
+                }
+            }
+            _ => self.clone(),
+        }
+    }
+}
+
+/// Why the server refused a client request
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum RejectReason {
+    NoActiveTurn,
+    NotYourTurn,
+    PlayerNotFound,
+    CardAlreadySelected,
+    EmptyHandSlot,
+    NoCardSelected,
+    NotSelectedCard, // The hand slot is not the one holding the selected card
+    WrongCard, // The selected card does not allow this action
+    TileOutOfBounds,
+    TileNotNearPlayer,
+    SameTile,
+    PositionOffBoard,
+    PositionUnreachable,
+    CancelNotToOrigin,
+    NotInFireLine,
+    GameInProgress,
+    InvalidPlayerCount,
+    InvalidBoardSize,
+    NotSeated,
+    NoEmptySeat,
+    MatchNotStarted,
+    NoReplay, // No match has been played in the room yet
+    MatchOver, // The match has ended, only a reset starts a new one
+    MatchNotOver, // Replays are only handed out once the match has ended
+}
+
+/// A game room listed by the lobby; the room id is also the id of its game channel
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub struct RoomInfo {
+    pub room_id: String, // Short code that doubles as the game channel id
+    pub players: Vec<String>,
+    pub is_private: bool,
+    pub player_count: usize, // Players needed to start the match
+    pub mode: GameMode,
+    pub board_size: BoardSize,
+}
+
+impl RoomInfo {
+    pub fn is_full(&self) -> bool {
+        self.players.len() >= self.player_count
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub enum LobbyToClient {
+    // Answers to Hello. Must stay the first variants so they decode the same in every version
+    HelloAccepted {
+        protocol_version: u32,
+    },
+    Incompatible {
+        protocol_version: u32, // The server's version
+        reason: IncompatibleReason,
+    },
+    RoomList {
+        rooms: Vec<RoomInfo>,
+    },
+    RoomJoined {
+        room_id: String,
+        is_private: bool,
+        player_count: usize,
+        mode: GameMode,
+        board_size: BoardSize,
+    },
+    JoinRejected {
+        room_id: String,
+        reason: JoinRejectReason,
+    },
+    DailyResults {
+        day: u64, // Days since the Unix epoch, in UTC
+        results: Vec<DailyResult>,
+    },
+}
+
+/// How one user's first daily dungeon of the day went
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub struct DailyResult {
+    pub user_id: String,
+    pub won: bool,
+    pub turns_taken: u32,
+    pub damage_taken: u32,
+}
+
+/// Why the lobby refused to put a user in a room
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum JoinRejectReason {
+    RoomNotFound,
+    RoomFull,
+}
+
+/// Why the server turned down a client's Hello
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum IncompatibleReason {
+    ClientOutdated, // The client was built against an older protocol
+    ServerOutdated, // The client is newer than the server it reached
+}
+
+/// Checks a client's protocol version against the one this build speaks
+pub fn check_protocol_version(protocol_version: u32) -> Result<(), IncompatibleReason> {
+    if protocol_version < PROTOCOL_VERSION {
+        return Err(IncompatibleReason::ClientOutdated);
+    }
+    if protocol_version > PROTOCOL_VERSION {
+        return Err(IncompatibleReason::ServerOutdated);
+    }
+    Ok(())
+}
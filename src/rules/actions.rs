@@ -0,0 +1,481 @@
+use crate::game::cards::card::Card;
+use crate::game::cards::card_effect::CardEffect;
+use crate::game::map::{ BoardSize, Player };
+use crate::game::map::board::{ locked_doors_at, unlock_door };
+use crate::game::map::tile::{ Tile, Direction };
+use crate::game::constants::{ DEBUG_MODE, FIREBALL_DAMAGE };
+use crate::protocol::{ ClientToServer, RejectReason };
+use super::{ Match, Turn, MatchEvent, MatchOutcome, FireballTarget, Rng };
+
+// The actions a player can take during their turn. Each one checks the request against the
+// turn and the board, applies it and returns what happened; nothing changes on an error.
+impl Match {
+    /// Plays a turn request from a client. Requests that are not turn actions change nothing.
+    pub fn play(
+        &mut self,
+        player_index: usize,
+        action: &ClientToServer,
+        rng: &mut impl Rng
+    ) -> Result<Vec<MatchEvent>, RejectReason> {
+        if self.is_over() {
+            return Err(RejectReason::MatchOver);
+        }
+        let player = player_index;
+        match *action {
+            ClientToServer::EndTurn => self.end_turn(player, rng),
+            ClientToServer::SelectCard { hand_index } => self.select_card(player, hand_index),
+            ClientToServer::CancelSelectCard { hand_index } => self.cancel_card(player, hand_index),
+            ClientToServer::ConfirmCard { hand_index } => {
+                self.confirm_card(player, hand_index, rng)
+            }
+            ClientToServer::RotateTile { tile_index } => self.rotate_tile(player, tile_index),
+            ClientToServer::MovePlayer { new_position, is_canceled } => {
+                self.move_player(player, new_position, is_canceled)
+            }
+            ClientToServer::SwapTiles { tile_index_1, tile_index_2 } => {
+                self.swap_tiles(player, tile_index_1, tile_index_2)
+            }
+            ClientToServer::FireballShot { target_tile, direction } => {
+                self.shoot_fireball(player, target_tile, direction, rng)
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Takes a card out of the player's hand and puts it into play
+    pub fn select_card(
+        &mut self,
+        player_index: usize,
+        hand_index: usize
+    ) -> Result<Vec<MatchEvent>, RejectReason> {
+        let turn = self.turn_of(player_index)?;
+        if turn.selected_card.is_some() {
+            return Err(RejectReason::CardAlreadySelected);
+        }
+        let player = self.players.get_mut(player_index).ok_or(RejectReason::PlayerNotFound)?;
+        let card = match player.hand.get_mut(hand_index) {
+            Some(card) if !card.is_dummy() => std::mem::replace(card, Card::dummy_card()),
+            _ => {
+                return Err(RejectReason::EmptyHandSlot);
+            }
+        };
+
+        self.turn = Some(Turn {
+            player_index,
+            selected_card: Some(card),
+            selected_card_index: hand_index,
+        });
+        Ok(Vec::new())
+    }
+
+    /// Undoes what the card in play did this turn and puts it back in the player's hand
+    pub fn cancel_card(
+        &mut self,
+        player_index: usize,
+        hand_index: usize
+    ) -> Result<Vec<MatchEvent>, RejectReason> {
+        let card = self.selected_card_at(player_index, hand_index)?;
+
+        match card.effect {
+            CardEffect::RotateCard => {
+                // Revert all tiles to their original rotation
+                for tile in self.tiles.iter_mut() {
+                    tile.rotate_entrances(tile.original_rotation);
+                    tile.current_rotation = tile.original_rotation;
+                }
+            }
+            CardEffect::SwapCard => {
+                // Put every tile back where it was at the start of the turn
+                self.tiles.sort_by_key(|tile| tile.original_location);
+            }
+            CardEffect::MoveOneTile => {
+                if let Some(player) = self.players.get_mut(player_index) {
+                    player.position = player.original_position;
+                }
+            }
+            _ => {}
+        }
+
+        let slot = self.players
+            .get_mut(player_index)
+            .and_then(|player| player.hand.get_mut(hand_index));
+        if let Some(slot) = slot {
+            *slot = card.clone();
+        }
+        self.turn = Some(Turn {
+            player_index,
+            selected_card: None,
+            selected_card_index: hand_index,
+        });
+        Ok(vec![MatchEvent::CardCancelled { player_index, card }])
+    }
+
+    /// Keeps what the card in play did and uses the card up
+    pub fn confirm_card(
+        &mut self,
+        player_index: usize,
+        hand_index: usize,
+        rng: &mut impl Rng
+    ) -> Result<Vec<MatchEvent>, RejectReason> {
+        let card = self.selected_card_at(player_index, hand_index)?;
+        Ok(self.confirm_selected_card(player_index, card, rng))
+    }
+
+    pub fn rotate_tile(
+        &mut self,
+        player_index: usize,
+        tile_index: usize
+    ) -> Result<Vec<MatchEvent>, RejectReason> {
+        let player = self.player_with_card(player_index, CardEffect::RotateCard)?;
+        check_tile_near_player(player, tile_index, self.board_size)?;
+
+        let tile = self.tiles.get_mut(tile_index).ok_or(RejectReason::TileOutOfBounds)?;
+        let new_rotation = (tile.current_rotation + 1) % 4;
+        tile.rotate_entrances(new_rotation);
+
+        let tile = tile.clone();
+        Ok(vec![MatchEvent::TileRotated { player_index, tile_index, tile }])
+    }
+
+    /// Moves the player to a connected tile, or back to where they started the turn when
+    /// `is_canceled` is set
+    pub fn move_player(
+        &mut self,
+        player_index: usize,
+        new_position: (usize, usize),
+        is_canceled: bool
+    ) -> Result<Vec<MatchEvent>, RejectReason> {
+        let player = self.player_with_card(player_index, CardEffect::MoveOneTile)?;
+        self.check_move(player, new_position, is_canceled)?;
+
+        if let Some(player) = self.players.get_mut(player_index) {
+            player.position = new_position;
+        }
+        let mut events = vec![MatchEvent::PlayerMoved { player_index, new_position, is_canceled }];
+        if !is_canceled {
+            events.extend(self.enter_portal(player_index));
+        }
+        Ok(events)
+    }
+
+    pub fn swap_tiles(
+        &mut self,
+        player_index: usize,
+        tile_index_1: usize,
+        tile_index_2: usize
+    ) -> Result<Vec<MatchEvent>, RejectReason> {
+        let player = self.player_with_card(player_index, CardEffect::SwapCard)?;
+        if tile_index_1 == tile_index_2 {
+            return Err(RejectReason::SameTile);
+        }
+        check_tile_near_player(player, tile_index_1, self.board_size)?;
+        check_tile_near_player(player, tile_index_2, self.board_size)?;
+        if tile_index_1 >= self.tiles.len() || tile_index_2 >= self.tiles.len() {
+            return Err(RejectReason::TileOutOfBounds);
+        }
+
+        self.tiles.swap(tile_index_1, tile_index_2);
+        Ok(vec![MatchEvent::TilesSwapped { tile_index_1, tile_index_2 }])
+    }
+
+    /// Shoots a fireball from the player's tile. The client only picks the direction by aiming
+    /// at a tile on the line; where the fireball stops is traced here.
+    pub fn shoot_fireball(
+        &mut self,
+        player_index: usize,
+        target_tile: usize,
+        direction: Direction,
+        rng: &mut impl Rng
+    ) -> Result<Vec<MatchEvent>, RejectReason> {
+        let player = self.player_with_card(player_index, CardEffect::FireCard)?;
+        if target_tile >= self.tiles.len() {
+            return Err(RejectReason::TileOutOfBounds);
+        }
+        let size = self.board_size;
+        let start_index = size.index(player.position.0, player.position.1);
+        let line = Tile::find_connected_line(start_index, direction, &self.tiles, size, None);
+        if target_tile == start_index || !line.contains(&target_tile) {
+            return Err(RejectReason::NotInFireLine);
+        }
+
+        let (end_tile_index, target) = self.trace_fireball(player_index, start_index, direction);
+
+        let mut events = Vec::new();
+        if !DEBUG_MODE {
+            let selected_card = self.turn.as_ref().and_then(|turn| turn.selected_card.clone());
+            if let Some(card) = selected_card {
+                events.extend(self.confirm_selected_card(player_index, card, rng));
+            }
+        }
+        events.push(MatchEvent::FireballShot {
+            player_index,
+            tile_index: start_index,
+            direction,
+            end_tile_index,
+            hits_target: target.is_some(),
+        });
+
+        if let Some(target) = target {
+            events.extend(self.resolve_fireball_hit(player_index, target));
+        }
+        Ok(events)
+    }
+
+    /// Makes what the card did this turn permanent, leaves an empty slot in the hand and puts
+    /// the card on the discard pile
+    pub(super) fn confirm_selected_card(
+        &mut self,
+        player_index: usize,
+        card: Card,
+        rng: &mut impl Rng
+    ) -> Vec<MatchEvent> {
+        match card.effect {
+            CardEffect::MoveOneTile => {
+                if let Some(player) = self.players.get_mut(player_index) {
+                    player.update_original_position();
+                }
+            }
+            CardEffect::RotateCard => {
+                for tile in self.tiles.iter_mut() {
+                    tile.original_rotation = tile.current_rotation;
+                }
+            }
+            CardEffect::SwapCard => {
+                for (current_index, tile) in self.tiles.iter_mut().enumerate() {
+                    tile.original_location = current_index;
+                }
+            }
+            _ => {}
+        }
+
+        // The card remembers its own slot, which is the one to empty
+        let slot = card.hand_index.and_then(|hand_index| {
+            self.players.get_mut(player_index)?.hand.get_mut(hand_index)
+        });
+        if let Some(slot) = slot {
+            *slot = Card::dummy_card();
+        }
+        self.discard(player_index, card.clone());
+
+        let turn = self.turn.as_mut().filter(|turn| turn.player_index == player_index);
+        if let Some(turn) = turn {
+            turn.selected_card = None;
+        }
+        let effect = card.effect.clone();
+        let hand_index = card.hand_index;
+        let mut events = vec![MatchEvent::CardConfirmed { player_index, card }];
+        if effect == CardEffect::MoveOneTile {
+            // Keys, doors and treasure only count once the move is kept, whether the player
+            // confirmed it or ended the turn with it
+            events.extend(self.use_keys(player_index));
+            if let Some(hand_index) = hand_index {
+                events.extend(self.loot_treasure(player_index, hand_index, rng));
+            }
+        }
+        // The exit can be swapped in under the babies as well as walked onto, and the match
+        // is only won once that move or swap is kept
+        if matches!(effect, CardEffect::MoveOneTile | CardEffect::SwapCard) {
+            events.extend(self.check_escape());
+        }
+        events
+    }
+
+    /// The card in play, if the request names the hand slot it came from
+    fn selected_card_at(
+        &self,
+        player_index: usize,
+        hand_index: usize
+    ) -> Result<Card, RejectReason> {
+        let turn = self.turn_of(player_index)?;
+        let card = turn.selected_card.clone().ok_or(RejectReason::NoCardSelected)?;
+        if hand_index != turn.selected_card_index {
+            return Err(RejectReason::NotSelectedCard);
+        }
+        Ok(card)
+    }
+
+    /// The player whose turn it is, if the card in play has the given effect
+    fn player_with_card(
+        &self,
+        player_index: usize,
+        effect: CardEffect
+    ) -> Result<&Player, RejectReason> {
+        let turn = self.turn_of(player_index)?;
+        let player = self.players.get(player_index).ok_or(RejectReason::PlayerNotFound)?;
+        let selected_effect = turn.selected_card.as_ref().map(|card| &card.effect);
+        if selected_effect != Some(&effect) {
+            return Err(RejectReason::WrongCard);
+        }
+        Ok(player)
+    }
+
+    /// Checks that the destination is reachable through connected entrances, or, for a
+    /// canceled move, is the position the player started the turn on
+    fn check_move(
+        &self,
+        player: &Player,
+        new_position: (usize, usize),
+        is_canceled: bool
+    ) -> Result<(), RejectReason> {
+        let size = self.board_size;
+        if !size.contains(new_position) {
+            return Err(RejectReason::PositionOffBoard);
+        }
+
+        if is_canceled {
+            if new_position != player.original_position {
+                return Err(RejectReason::CancelNotToOrigin);
+            }
+            return Ok(());
+        }
+
+        let start_index = size.index(player.position.0, player.position.1);
+        let target_index = size.index(new_position.0, new_position.1);
+        let reachable = self.tiles[start_index].find_reachable_tiles(
+            start_index,
+            &self.tiles,
+            size
+        );
+        if !reachable.contains(&target_index) {
+            return Err(RejectReason::PositionUnreachable);
+        }
+        Ok(())
+    }
+
+    /// Follows a fireball from the shooter's tile until it hits a wall, a player or the monster.
+    /// Returns the tile the fireball stops on and what it hit, if anything.
+    fn trace_fireball(
+        &self,
+        shooter_index: usize,
+        start_index: usize,
+        direction: Direction
+    ) -> (usize, Option<FireballTarget>) {
+        let tiles = &self.tiles;
+        let size = self.board_size;
+        let mut current_index = start_index;
+
+        while !tiles[current_index].would_fireball_hit_wall(current_index, direction, tiles, size) {
+            current_index = match size.index_in_direction(current_index, direction) {
+                Some(index) => index,
+                None => break,
+            };
+            let position = size.position(current_index);
+
+            // Players are checked before the monster, matching the client animation
+            let hit_player = self.players
+                .iter()
+                .enumerate()
+                .find(|(i, p)| *i != shooter_index && p.position == position)
+                .map(|(i, _)| i);
+            if let Some(player_index) = hit_player {
+                return (current_index, Some(FireballTarget::Player(player_index)));
+            }
+
+            let hit_monster = self.monster
+                .as_ref()
+                .is_some_and(|monster| monster.is_alive() && monster.position == position);
+            if hit_monster {
+                return (current_index, Some(FireballTarget::Monster));
+            }
+        }
+
+        (current_index, None)
+    }
+
+    /// Applies fireball damage to whatever the trace hit
+    fn resolve_fireball_hit(
+        &mut self,
+        shooter_index: usize,
+        target: FireballTarget
+    ) -> Vec<MatchEvent> {
+        let mut events = vec![MatchEvent::FireballHit {
+            player_index: shooter_index,
+            target,
+            damage: FIREBALL_DAMAGE,
+        }];
+
+        let match_ended = match target {
+            FireballTarget::Monster => {
+                match &mut self.monster {
+                    Some(monster) => {
+                        monster.take_damage(FIREBALL_DAMAGE);
+                        !monster.is_alive()
+                    }
+                    None => false,
+                }
+            }
+            FireballTarget::Player(target_index) => {
+                self.record_damage(target_index, FIREBALL_DAMAGE);
+                match self.players.get_mut(target_index) {
+                    Some(player) => {
+                        player.take_damage(FIREBALL_DAMAGE);
+                        !player.is_alive()
+                    }
+                    None => false,
+                }
+            }
+        };
+
+        if match_ended {
+            // Beating the monster wins the match for everyone, losing a baby loses it
+            let outcome = match target {
+                FireballTarget::Monster => MatchOutcome::MonsterSlain,
+                FireballTarget::Player(_) => MatchOutcome::BabyLost,
+            };
+            events.extend(self.end_match(outcome));
+        }
+        events
+    }
+
+    /// Picks up the key on the player's tile, then opens the locked doors around it for as
+    /// long as the player has keys. Done when a move is confirmed, so a cancelled move leaves
+    /// the keys and doors as they were.
+    fn use_keys(&mut self, player_index: usize) -> Vec<MatchEvent> {
+        let mut events = Vec::new();
+        let size = self.board_size;
+        let tile_index = match self.player_tile(player_index) {
+            Some(tile_index) => tile_index,
+            None => {
+                return events;
+            }
+        };
+
+        if self.tiles[tile_index].has_key {
+            self.tiles[tile_index].has_key = false;
+            self.players[player_index].keys += 1;
+            events.push(MatchEvent::KeyPickedUp { player_index, tile_index });
+        }
+
+        for direction in locked_doors_at(&self.tiles, size, tile_index) {
+            if self.players[player_index].keys == 0 {
+                break;
+            }
+            self.players[player_index].keys -= 1;
+            unlock_door(&mut self.tiles, size, tile_index, direction);
+            events.push(MatchEvent::DoorUnlocked { player_index, tile_index, direction });
+        }
+        events
+    }
+
+    /// Ends the match as won once the babies have all made it to the exit
+    fn check_escape(&mut self) -> Option<MatchEvent> {
+        if !self.has_escaped() {
+            return None;
+        }
+        self.end_match(MatchOutcome::Escaped)
+    }
+}
+
+/// Rotate and swap cards only affect the player's tile and the tiles around it
+fn check_tile_near_player(
+    player: &Player,
+    tile_index: usize,
+    size: BoardSize
+) -> Result<(), RejectReason> {
+    let player_index = size.index(player.position.0, player.position.1);
+    if Tile::get_adjacent_indices(player_index, size, true, true).contains(&tile_index) {
+        Ok(())
+    } else {
+        Err(RejectReason::TileNotNearPlayer)
+    }
+}
@@ -0,0 +1,350 @@
+// The game rules, kept apart from drawing, networking and the engine. A `Match` holds the
+// board and turn state, each action checks and applies one player request, and the
+// `MatchEvent`s it returns are what the server channel broadcasts and clients animate.
+// Players are referred to by seat index; mapping seats to users is up to the caller.
+use serde::{ Serialize, Deserialize };
+use borsh::{ BorshSerialize, BorshDeserialize };
+use crate::game::map::{ Tile, Player, PlayerId, Monster, BoardSize };
+use crate::game::map::tile::Direction;
+use crate::game::map::board::random_tiles;
+use crate::game::cards::card::Card;
+use crate::game::cards::deck::Deck;
+use crate::game::constants::HAND_SIZE;
+use crate::protocol::RejectReason;
+
+mod actions;
+mod rng;
+mod replay;
+mod rooms;
+#[cfg(test)]
+mod tests;
+
+pub use rng::{ Rng, SeededRng, daily_seed };
+pub use replay::{ Replay, ReplayStep };
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub struct Match {
+    pub board_size: BoardSize,
+    pub tiles: Vec<Tile>, // Row by row, board_size.width tiles per row
+    pub players: Vec<Player>, // One per seat, in turn order
+    pub decks: Vec<Deck>, // One per seat, the cards each player owns outside their hand
+    pub monster: Option<Monster>, // Spawned when the match starts
+    pub turn_index: usize, // Seat that plays next, kept while the match is paused
+    pub turn: Option<Turn>, // None before the start and while the match is paused
+    pub stats: Vec<PlayerStats>, // One per seat, for the daily results
+    pub outcome: Option<MatchOutcome>, // Set when the match ends; no turn is played after that
+}
+
+/// How a seat's match went so far
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PlayerStats {
+    pub turns_taken: u32,
+    pub damage_taken: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub struct Turn {
+    pub player_index: usize,
+    pub selected_card: Option<Card>, // Taken out of the player's hand until confirmed or cancelled
+    pub selected_card_index: usize,
+}
+
+/// What a fireball stopped on
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum FireballTarget {
+    Monster,
+    Player(usize),
+}
+
+/// How a match ended
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum MatchOutcome {
+    MonsterSlain, // A fireball finished off the monster
+    Escaped, // Every baby still alive made it onto the exit
+    BabyLost, // A baby was killed by the monster or a fireball
+}
+
+impl MatchOutcome {
+    /// The match is cooperative, every seat wins or loses together
+    pub fn players_won(&self) -> bool {
+        match self {
+            MatchOutcome::MonsterSlain | MatchOutcome::Escaped => true,
+            MatchOutcome::BabyLost => false,
+        }
+    }
+}
+
+/// Something that happened in a match, for the caller to pass on
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub enum MatchEvent {
+    TurnStarted {
+        player_index: usize,
+    },
+    CardCancelled {
+        player_index: usize,
+        card: Card,
+    },
+    CardConfirmed {
+        player_index: usize,
+        card: Card,
+    },
+    TileRotated {
+        player_index: usize,
+        tile_index: usize,
+        tile: Tile,
+    },
+    PlayerMoved {
+        player_index: usize,
+        new_position: (usize, usize),
+        is_canceled: bool,
+    },
+    TilesSwapped {
+        tile_index_1: usize,
+        tile_index_2: usize,
+    },
+    FireballShot {
+        player_index: usize,
+        tile_index: usize,
+        direction: Direction,
+        end_tile_index: usize,
+        hits_target: bool,
+    },
+    FireballHit {
+        player_index: usize,
+        target: FireballTarget,
+        damage: u32,
+    },
+    MonsterAttacked {
+        player_index: usize,
+        damage: u32,
+    },
+    KeyPickedUp {
+        player_index: usize,
+        tile_index: usize,
+    },
+    PlayerBurned {
+        player_index: usize,
+        damage: u32,
+    },
+    PlayerHealed {
+        player_index: usize,
+        amount: u32,
+    },
+    PlayerTeleported {
+        player_index: usize,
+        new_position: (usize, usize),
+    },
+    TreasureFound {
+        player_index: usize,
+        tile_index: usize,
+        card: Card,
+    },
+    DoorUnlocked {
+        player_index: usize,
+        tile_index: usize,
+        direction: Direction,
+    },
+    MatchEnded {
+        outcome: MatchOutcome,
+    },
+}
+
+impl Match {
+    /// A fresh board of the given size for the given number of seats; the monster spawns once
+    /// the match starts
+    pub fn new(player_count: usize, board_size: BoardSize, rng: &mut impl Rng) -> Self {
+        Self {
+            board_size,
+            tiles: random_tiles(board_size, rng),
+            players: new_players(player_count, board_size, rng),
+            decks: (0..player_count).map(|_| Deck::new(rng)).collect(),
+            monster: None,
+            turn_index: 0,
+            turn: None,
+            stats: vec![PlayerStats::default(); player_count],
+            outcome: None,
+        }
+    }
+
+    /// Returns true once the monster is on the board, until the next reset
+    pub fn has_started(&self) -> bool {
+        self.monster.is_some()
+    }
+
+    /// Returns true once the match has ended, until the next reset
+    pub fn is_over(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    /// Spawns the monster and has every player draw a hand from their deck
+    pub fn start(&mut self, rng: &mut impl Rng) {
+        self.turn_index = 0;
+        self.monster = Some(Monster::new(self.board_size));
+        for player_index in 0..self.players.len() {
+            self.refill_hand(player_index, rng);
+        }
+    }
+
+    /// Gives the seat in `turn_index` its turn, to start or resume the match
+    pub fn begin_turn(&mut self) -> Vec<MatchEvent> {
+        if self.is_over() || self.turn_index >= self.players.len() {
+            return Vec::new();
+        }
+        self.turn = Some(Turn {
+            player_index: self.turn_index,
+            selected_card: None,
+            selected_card_index: 0,
+        });
+        vec![MatchEvent::TurnStarted { player_index: self.turn_index }]
+    }
+
+    /// Holds the match until `begin_turn`. A card that was mid-play goes back to its owner's
+    /// hand, so the resumed turn starts over.
+    pub fn pause(&mut self) -> Vec<MatchEvent> {
+        let mut events = Vec::new();
+        if let Some(turn) = self.turn.clone().filter(|turn| turn.selected_card.is_some()) {
+            let cancelled = self.cancel_card(turn.player_index, turn.selected_card_index);
+            events.extend(cancelled.unwrap_or_default());
+        }
+        self.turn = None;
+        events
+    }
+
+    /// Ends the player's turn: a card still in play is confirmed, the player draws back up to
+    /// a full hand and feels the room they are in, the monster moves, and the next seat's turn
+    /// begins unless the match is over.
+    pub fn end_turn(
+        &mut self,
+        player_index: usize,
+        rng: &mut impl Rng
+    ) -> Result<Vec<MatchEvent>, RejectReason> {
+        let turn = self.turn_of(player_index)?;
+        let mut events = Vec::new();
+        if let Some(card) = turn.selected_card.clone() {
+            events.extend(self.confirm_selected_card(player_index, card, rng));
+        }
+        // Keeping the card in play can be what gets the babies out
+        if self.is_over() {
+            return Ok(events);
+        }
+
+        self.refill_hand(player_index, rng);
+        if let Some(stats) = self.stats.get_mut(player_index) {
+            stats.turns_taken += 1;
+        }
+        events.extend(self.end_turn_in_room(player_index));
+
+        // Monster takes its turn after player
+        let size = self.board_size;
+        let monster_hit = self.monster.as_mut().and_then(|monster| {
+            let player_id = monster.take_turn(&mut self.players, &self.tiles, size, rng)?;
+            Some((player_id, monster.damage))
+        });
+        if let Some((player_id, damage)) = monster_hit {
+            self.record_damage(player_id.index(), damage);
+            events.push(MatchEvent::MonsterAttacked { player_index: player_id.index(), damage });
+        }
+
+        if self.players.iter().any(|player| !player.is_alive()) {
+            events.extend(self.end_match(MatchOutcome::BabyLost));
+            return Ok(events);
+        }
+
+        self.turn_index = (self.turn_index + 1) % self.players.len();
+        events.extend(self.begin_turn());
+        Ok(events)
+    }
+
+    /// Fills the empty slots of a player's hand from their deck. Cards still in the hand are
+    /// kept; slots stay empty once the whole deck is in hand.
+    pub fn refill_hand(&mut self, player_index: usize, rng: &mut impl Rng) {
+        let seat = (self.players.get_mut(player_index), self.decks.get_mut(player_index));
+        let (player, deck) = match seat {
+            (Some(player), Some(deck)) => (player, deck),
+            _ => {
+                return;
+            }
+        };
+        player.hand.resize_with(HAND_SIZE, Card::dummy_card);
+        for (hand_index, slot) in player.hand.iter_mut().enumerate() {
+            if !slot.is_dummy() {
+                continue;
+            }
+            if let Some(mut card) = deck.draw(rng) {
+                card.hand_index = Some(hand_index);
+                *slot = card;
+            }
+        }
+        self.update_pile_counts(player_index);
+    }
+
+    /// Puts a played card on the player's discard pile
+    pub(super) fn discard(&mut self, player_index: usize, card: Card) {
+        if let Some(deck) = self.decks.get_mut(player_index) {
+            deck.discard(card);
+        }
+        self.update_pile_counts(player_index);
+    }
+
+    /// Copies the size of the player's piles onto the player, which is what clients see
+    fn update_pile_counts(&mut self, player_index: usize) {
+        let seat = (self.players.get_mut(player_index), self.decks.get(player_index));
+        if let (Some(player), Some(deck)) = seat {
+            player.draw_pile_count = deck.draw_count();
+            player.discard_pile_count = deck.discard_count();
+        }
+    }
+
+    /// Returns true once every baby still alive stands on the exit tile
+    pub fn has_escaped(&self) -> bool {
+        let exit_position = self.tiles
+            .iter()
+            .position(|tile| tile.is_exit)
+            .map(|exit_index| self.board_size.position(exit_index));
+        let mut alive = self.players.iter().filter(|player| player.is_alive()).peekable();
+        alive.peek().is_some() && alive.all(|player| Some(player.position) == exit_position)
+    }
+
+    /// Ends the match with the given outcome and takes the turn away, so nothing more can be
+    /// played. A match only ends once.
+    fn end_match(&mut self, outcome: MatchOutcome) -> Option<MatchEvent> {
+        if self.is_over() {
+            return None;
+        }
+        self.outcome = Some(outcome);
+        self.turn = None;
+        Some(MatchEvent::MatchEnded { outcome })
+    }
+
+    fn record_damage(&mut self, player_index: usize, damage: u32) {
+        if let Some(stats) = self.stats.get_mut(player_index) {
+            stats.damage_taken += damage;
+        }
+    }
+
+    /// Index of the tile the player stands on
+    fn player_tile(&self, player_index: usize) -> Option<usize> {
+        let (x, y) = self.players.get(player_index)?.position;
+        Some(self.board_size.index(x, y))
+    }
+
+    /// The current turn, if it belongs to this player
+    fn turn_of(&self, player_index: usize) -> Result<&Turn, RejectReason> {
+        let turn = self.turn.as_ref().ok_or(RejectReason::NoActiveTurn)?;
+        if turn.player_index != player_index {
+            return Err(RejectReason::NotYourTurn);
+        }
+        Ok(turn)
+    }
+}
+
+/// One player per seat, each in their own corner with an empty hand until the match starts
+fn new_players(player_count: usize, size: BoardSize, rng: &mut impl Rng) -> Vec<Player> {
+    (0..player_count)
+        .filter_map(PlayerId::from_index)
+        .map(|player_id| {
+            let (x, y) = player_id.spawn_position(size);
+            Player::new(player_id, x, y, Player::new_hand(HAND_SIZE, true, rng))
+        })
+        .collect()
+}
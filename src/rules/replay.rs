@@ -0,0 +1,52 @@
+use serde::{ Serialize, Deserialize };
+use borsh::{ BorshSerialize, BorshDeserialize };
+use crate::protocol::ClientToServer;
+use super::{ Match, SeededRng };
+
+/// Everything needed to play a match again: the board as it was dealt, the generator in the
+/// state it was in at that point, and every step that changed the match since, in order.
+/// The rules are deterministic, so playing the steps back gives the same match.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub struct Replay {
+    pub players: Vec<String>, // Who played each seat, for showing names
+    pub start: Match, // Started, before the first turn begins
+    pub rng: SeededRng,
+    pub steps: Vec<ReplayStep>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub enum ReplayStep {
+    // A turn action the match accepted
+    Action {
+        player_index: usize,
+        action: ClientToServer,
+    },
+    // The match waited for a seat to be filled again, then resumed
+    Paused,
+}
+
+impl Replay {
+    pub fn new(start: &Match, rng: &SeededRng, players: &[String]) -> Self {
+        Self {
+            players: players.to_vec(),
+            start: start.clone(),
+            rng: rng.clone(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// The seed the match was dealt from
+    pub fn seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    /// The compact form, for storing and sending
+    pub fn to_borsh(&self) -> Vec<u8> {
+        borsh::to_vec(self).unwrap_or_default()
+    }
+
+    /// A readable form, for looking into a match by hand
+    pub fn to_json(&self) -> String {
+        turbo::serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
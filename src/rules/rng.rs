@@ -0,0 +1,63 @@
+use serde::{ Serialize, Deserialize };
+use borsh::{ BorshSerialize, BorshDeserialize };
+
+/// Where the rules get their randomness from. The server plugs in a `SeededRng`; anything
+/// implementing this can drive a match, so the rules never call the engine directly.
+pub trait Rng {
+    fn next_u32(&mut self) -> u32;
+
+    /// A float in `0.0..1.0`
+    fn next_f32(&mut self) -> f32 {
+        ((self.next_u32() >> 8) as f32) / ((1u32 << 24) as f32)
+    }
+
+    /// An index in `0..len`; `len` must not be zero
+    fn pick(&mut self, len: usize) -> usize {
+        (self.next_u32() as usize) % len
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) where Self: Sized {
+        for i in (1..items.len()).rev() {
+            let j = self.pick(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+/// A small deterministic generator (SplitMix64). The same seed always deals the same board,
+/// hands and monster moves, so a match can be played again from its seed.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub struct SeededRng {
+    seed: u64,
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// The seed this generator started from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// The seed of the daily dungeon, the same for everyone on the given day
+pub fn daily_seed(day: u64) -> u64 {
+    SeededRng::new(day).next_u64()
+}
+
+impl Rng for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+}
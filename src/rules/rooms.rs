@@ -0,0 +1,60 @@
+use crate::game::map::tile::TileKind;
+use crate::game::map::board::paired_portal;
+use crate::game::constants::{ LAVA_DAMAGE, FOUNTAIN_HEAL };
+use super::{ Match, MatchEvent, Rng };
+
+// What the special rooms do to the players in them. Lava and fountains act on a player ending
+// their turn in the room, a portal as soon as a player walks in, and a treasure room once the
+// move into it is confirmed. The room kind belongs to the tile, so it follows swaps.
+impl Match {
+    /// Burns or heals the player for the room they end their turn in
+    pub(super) fn end_turn_in_room(&mut self, player_index: usize) -> Option<MatchEvent> {
+        let tile_index = self.player_tile(player_index)?;
+        let player = &mut self.players[player_index];
+        match self.tiles[tile_index].kind {
+            TileKind::Lava => {
+                player.take_damage(LAVA_DAMAGE);
+                self.record_damage(player_index, LAVA_DAMAGE);
+                Some(MatchEvent::PlayerBurned { player_index, damage: LAVA_DAMAGE })
+            }
+            TileKind::Fountain => {
+                let health_before = player.health;
+                player.heal(FOUNTAIN_HEAL);
+                let amount = player.health - health_before;
+                (amount > 0).then_some(MatchEvent::PlayerHealed { player_index, amount })
+            }
+            _ => None,
+        }
+    }
+
+    /// Sends a player who walked into a portal out of the other portal of its pair
+    pub(super) fn enter_portal(&mut self, player_index: usize) -> Option<MatchEvent> {
+        let tile_index = self.player_tile(player_index)?;
+        let exit_index = paired_portal(&self.tiles, tile_index)?;
+        let new_position = self.board_size.position(exit_index);
+        self.players[player_index].position = new_position;
+        Some(MatchEvent::PlayerTeleported { player_index, new_position })
+    }
+
+    /// Hands out the bonus card of the treasure room the player confirmed a move into: the
+    /// next card of their own deck, drawn now instead of at the end of the turn, into the hand
+    /// slot the move card left empty. The room is a plain corridor after.
+    pub(super) fn loot_treasure(
+        &mut self,
+        player_index: usize,
+        hand_index: usize,
+        rng: &mut impl Rng
+    ) -> Option<MatchEvent> {
+        let tile_index = self.player_tile(player_index)?;
+        if self.tiles[tile_index].kind != TileKind::Treasure {
+            return None;
+        }
+        let slot = self.players[player_index].hand.get_mut(hand_index)?;
+        let mut card = self.decks.get_mut(player_index)?.draw(rng)?;
+        card.hand_index = Some(hand_index);
+        *slot = card.clone();
+        self.update_pile_counts(player_index);
+        self.tiles[tile_index].kind = TileKind::Corridor;
+        Some(MatchEvent::TreasureFound { player_index, tile_index, card })
+    }
+}
@@ -0,0 +1,748 @@
+use super::*;
+use crate::game::cards::card_effect::CardEffect;
+use crate::game::cards::deck::Deck;
+use crate::game::map::tile::TileKind;
+use crate::game::constants::{
+    DECK_COPIES,
+    FIREBALL_DAMAGE,
+    FOUNTAIN_HEAL,
+    HAND_SIZE,
+    LAVA_DAMAGE,
+    PLAYER_HEALTH,
+};
+use crate::protocol::ClientToServer;
+
+const SEED: u64 = 42;
+
+/// A board of the given size where every tile has no entrances at all
+fn walled_board(size: BoardSize) -> Vec<Tile> {
+    (0..size.tile_count())
+        .map(|index| {
+            let mut tile = Tile::new(Vec::new());
+            tile.original_location = index;
+            tile
+        })
+        .collect()
+}
+
+/// A board of the given size where every tile connects to all of its neighbours
+fn open_board(size: BoardSize) -> Vec<Tile> {
+    let mut tiles = walled_board(size);
+    for index in 0..tiles.len() {
+        for direction in [Direction::Down, Direction::Right] {
+            if size.index_in_direction(index, direction).is_some() {
+                connect(&mut tiles, size, index, direction);
+            }
+        }
+    }
+    tiles
+}
+
+/// Opens the passage out of a tile in the given direction, on both sides
+fn connect(tiles: &mut [Tile], size: BoardSize, index: usize, direction: Direction) {
+    let neighbour = size.index_in_direction(index, direction).expect("neighbour on the board");
+    tiles[index].entrances.push(direction);
+    tiles[neighbour].entrances.push(direction.opposite());
+}
+
+/// One of each card, in a known order
+fn known_hand() -> Vec<Card> {
+    let mut hand = vec![
+        Card::move_card(1),
+        Card::rotate_card(2),
+        Card::swap_card(3),
+        Card::fire_card(4)
+    ];
+    for (hand_index, card) in hand.iter_mut().enumerate() {
+        card.hand_index = Some(hand_index);
+    }
+    hand
+}
+
+/// A started two-seat match on the given tiles, with seat 0 to play and known hands
+fn started_match(size: BoardSize, tiles: Vec<Tile>) -> (Match, SeededRng) {
+    let mut rng = SeededRng::new(SEED);
+    let mut game = Match::new(2, size, &mut rng);
+    game.tiles = tiles;
+    game.start(&mut rng);
+    for player in game.players.iter_mut() {
+        player.hand = known_hand();
+    }
+    game.begin_turn();
+    (game, rng)
+}
+
+/// Every card a seat owns: in hand, in play, and in both piles of their deck
+fn cards_owned(game: &Match, player_index: usize) -> usize {
+    let in_hand = game.players[player_index].hand
+        .iter()
+        .filter(|card| !card.is_dummy())
+        .count();
+    let in_play = game.turn
+        .as_ref()
+        .filter(|turn| turn.player_index == player_index)
+        .map_or(0, |turn| usize::from(turn.selected_card.is_some()));
+    let deck = &game.decks[player_index];
+    in_hand + in_play + deck.draw_count() + deck.discard_count()
+}
+
+/// Selects seat 0's card with the given effect and returns its hand slot
+fn select(game: &mut Match, effect: CardEffect) -> usize {
+    let hand_index = game.players[0].hand
+        .iter()
+        .position(|card| card.effect == effect)
+        .expect("card in hand");
+    game.select_card(0, hand_index).expect("card selected");
+    hand_index
+}
+
+#[test]
+fn selecting_takes_the_card_out_of_the_hand() {
+    let size = BoardSize::default();
+    let (mut game, _) = started_match(size, open_board(size));
+
+    let hand_index = select(&mut game, CardEffect::MoveOneTile);
+
+    let turn = game.turn.as_ref().expect("turn");
+    assert_eq!(turn.selected_card.as_ref().map(|card| card.id), Some(1));
+    assert_eq!(turn.selected_card_index, hand_index);
+    assert!(game.players[0].hand[hand_index].is_dummy());
+}
+
+#[test]
+fn selecting_is_refused_out_of_turn_and_on_empty_or_busy_slots() {
+    let size = BoardSize::default();
+    let (mut game, mut rng) = started_match(size, open_board(size));
+
+    assert_eq!(game.select_card(1, 0).unwrap_err(), RejectReason::NotYourTurn);
+
+    let hand_index = select(&mut game, CardEffect::MoveOneTile);
+    assert_eq!(game.select_card(0, 1).unwrap_err(), RejectReason::CardAlreadySelected);
+
+    game.confirm_card(0, hand_index, &mut rng).expect("confirmed");
+    assert_eq!(game.select_card(0, hand_index).unwrap_err(), RejectReason::EmptyHandSlot);
+}
+
+#[test]
+fn cancelling_puts_the_card_back() {
+    let size = BoardSize::default();
+    let (mut game, _) = started_match(size, open_board(size));
+    let hand_index = select(&mut game, CardEffect::MoveOneTile);
+
+    let events = game.cancel_card(0, hand_index).expect("cancelled");
+
+    assert!(matches!(events[..], [MatchEvent::CardCancelled { player_index: 0, .. }]));
+    assert_eq!(game.players[0].hand[hand_index].id, 1);
+    assert!(game.turn.as_ref().is_some_and(|turn| turn.selected_card.is_none()));
+}
+
+#[test]
+fn cancelling_a_move_returns_the_player_to_the_start() {
+    let size = BoardSize::default();
+    let (mut game, _) = started_match(size, open_board(size));
+    let hand_index = select(&mut game, CardEffect::MoveOneTile);
+    game.move_player(0, (1, 0), false).expect("moved");
+
+    game.cancel_card(0, hand_index).expect("cancelled");
+
+    assert_eq!(game.players[0].position, (0, 0));
+}
+
+#[test]
+fn confirming_uses_the_card_up() {
+    let size = BoardSize::default();
+    let (mut game, mut rng) = started_match(size, open_board(size));
+    let hand_index = select(&mut game, CardEffect::MoveOneTile);
+    game.move_player(0, (1, 0), false).expect("moved");
+
+    let events = game.confirm_card(0, hand_index, &mut rng).expect("confirmed");
+
+    assert!(matches!(events[0], MatchEvent::CardConfirmed { player_index: 0, .. }));
+    assert!(game.players[0].hand[hand_index].is_dummy());
+    assert_eq!(game.players[0].original_position, (1, 0));
+    assert_eq!(game.players[0].discard_pile_count, 1);
+}
+
+#[test]
+fn moves_follow_connected_entrances() {
+    let size = BoardSize::default();
+    let mut tiles = walled_board(size);
+    // (0, 0) -> (1, 0) -> (1, 1), nothing else is open
+    connect(&mut tiles, size, size.index(0, 0), Direction::Right);
+    connect(&mut tiles, size, size.index(1, 0), Direction::Down);
+    let (mut game, _) = started_match(size, tiles);
+    select(&mut game, CardEffect::MoveOneTile);
+
+    let result = game.move_player(0, (0, 1), false);
+    assert_eq!(result.unwrap_err(), RejectReason::PositionUnreachable);
+    let result = game.move_player(0, (size.width, 0), false);
+    assert_eq!(result.unwrap_err(), RejectReason::PositionOffBoard);
+
+    game.move_player(0, (1, 1), false).expect("two tiles along the path");
+    assert_eq!(game.players[0].position, (1, 1));
+}
+
+#[test]
+fn locked_doors_block_moves() {
+    let size = BoardSize::default();
+    let mut tiles = walled_board(size);
+    connect(&mut tiles, size, size.index(0, 0), Direction::Right);
+    tiles[size.index(0, 0)].locked.push(Direction::Right);
+    let (mut game, _) = started_match(size, tiles);
+    select(&mut game, CardEffect::MoveOneTile);
+
+    let result = game.move_player(0, (1, 0), false);
+
+    assert_eq!(result.unwrap_err(), RejectReason::PositionUnreachable);
+}
+
+#[test]
+fn a_key_opens_the_locked_doors_around_its_tile() {
+    let size = BoardSize::default();
+    let mut tiles = open_board(size);
+    let key_tile = size.index(1, 0);
+    tiles[key_tile].has_key = true;
+    tiles[size.index(2, 0)].locked.push(Direction::Left);
+    let (mut game, mut rng) = started_match(size, tiles);
+    let hand_index = select(&mut game, CardEffect::MoveOneTile);
+    game.move_player(0, (1, 0), false).expect("moved");
+
+    let events = game.confirm_card(0, hand_index, &mut rng).expect("confirmed");
+
+    let picked_up = events.iter().any(|event| {
+        matches!(event, MatchEvent::KeyPickedUp { player_index: 0, .. })
+    });
+    let unlocked = events.iter().any(|event| {
+        matches!(event, MatchEvent::DoorUnlocked { direction: Direction::Right, .. })
+    });
+    assert!(picked_up && unlocked);
+    assert!(!game.tiles[key_tile].has_key);
+    assert!(game.tiles[size.index(2, 0)].locked.is_empty());
+    assert_eq!(game.players[0].keys, 0);
+}
+
+#[test]
+fn a_cancelled_move_leaves_the_key_behind() {
+    let size = BoardSize::default();
+    let mut tiles = open_board(size);
+    let key_tile = size.index(1, 0);
+    tiles[key_tile].has_key = true;
+    let (mut game, _) = started_match(size, tiles);
+    let hand_index = select(&mut game, CardEffect::MoveOneTile);
+    game.move_player(0, (1, 0), false).expect("moved");
+
+    game.cancel_card(0, hand_index).expect("cancelled");
+
+    assert!(game.tiles[key_tile].has_key);
+    assert_eq!(game.players[0].keys, 0);
+}
+
+#[test]
+fn locked_doors_turn_with_their_tile() {
+    let mut tile = Tile::new(vec![Direction::Up, Direction::Left]);
+    tile.locked.push(Direction::Up);
+
+    tile.rotate_entrances(1);
+
+    assert_eq!(tile.entrances, vec![Direction::Right, Direction::Up]);
+    assert_eq!(tile.locked, vec![Direction::Right]);
+    assert!(tile.is_open(Direction::Up));
+    assert!(!tile.is_open(Direction::Right));
+}
+
+#[test]
+fn a_cancelled_move_can_only_go_back_to_the_start() {
+    let size = BoardSize::default();
+    let (mut game, _) = started_match(size, open_board(size));
+    select(&mut game, CardEffect::MoveOneTile);
+
+    let result = game.move_player(0, (1, 0), true);
+
+    assert_eq!(result.unwrap_err(), RejectReason::CancelNotToOrigin);
+}
+
+#[test]
+fn moving_needs_a_move_card_in_play() {
+    let size = BoardSize::default();
+    let (mut game, _) = started_match(size, open_board(size));
+    select(&mut game, CardEffect::RotateCard);
+
+    let result = game.move_player(0, (1, 0), false);
+
+    assert_eq!(result.unwrap_err(), RejectReason::WrongCard);
+}
+
+#[test]
+fn fireballs_stop_at_walls() {
+    let size = BoardSize::default();
+    let mut tiles = walled_board(size);
+    connect(&mut tiles, size, size.index(0, 0), Direction::Right);
+    connect(&mut tiles, size, size.index(1, 0), Direction::Right);
+    let (mut game, mut rng) = started_match(size, tiles);
+    select(&mut game, CardEffect::FireCard);
+
+    let events = game.shoot_fireball(0, 1, Direction::Right, &mut rng).expect("shot");
+
+    let shot = events.iter().find_map(|event| {
+        match event {
+            MatchEvent::FireballShot { end_tile_index, hits_target, .. } => {
+                Some((*end_tile_index, *hits_target))
+            }
+            _ => None,
+        }
+    });
+    assert_eq!(shot, Some((2, false)));
+    assert!(!events.iter().any(|event| matches!(event, MatchEvent::FireballHit { .. })));
+}
+
+#[test]
+fn fireballs_hit_the_first_player_in_line() {
+    let size = BoardSize::default();
+    let (mut game, mut rng) = started_match(size, open_board(size));
+    game.players[1].position = (3, 0);
+    select(&mut game, CardEffect::FireCard);
+
+    let events = game.shoot_fireball(0, 1, Direction::Right, &mut rng).expect("shot");
+
+    let hit = events.iter().any(|event| {
+        matches!(event, MatchEvent::FireballHit { target: FireballTarget::Player(1), .. })
+    });
+    assert!(hit);
+    assert_eq!(game.players[1].health, PLAYER_HEALTH - FIREBALL_DAMAGE);
+    assert_eq!(game.stats[1].damage_taken, FIREBALL_DAMAGE);
+}
+
+#[test]
+fn fireballs_must_be_aimed_along_a_connected_line() {
+    let size = BoardSize::default();
+    let (mut game, mut rng) = started_match(size, walled_board(size));
+    select(&mut game, CardEffect::FireCard);
+
+    let result = game.shoot_fireball(0, 1, Direction::Right, &mut rng);
+
+    assert_eq!(result.unwrap_err(), RejectReason::NotInFireLine);
+}
+
+#[test]
+fn slaying_the_monster_wins_the_match_and_takes_the_turn_away() {
+    let size = BoardSize::default();
+    let (mut game, mut rng) = started_match(size, open_board(size));
+    let (center_x, center_y) = size.center();
+    game.players[0].position = (0, center_y);
+    if let Some(monster) = game.monster.as_mut() {
+        monster.health = FIREBALL_DAMAGE;
+    }
+    select(&mut game, CardEffect::FireCard);
+
+    let target_tile = size.index(center_x, center_y);
+    let events = game.shoot_fireball(0, target_tile, Direction::Right, &mut rng).expect("shot");
+
+    let hit = events.iter().any(|event| {
+        matches!(event, MatchEvent::FireballHit { target: FireballTarget::Monster, .. })
+    });
+    assert!(hit);
+    let outcome = events.iter().find_map(|event| {
+        match event {
+            MatchEvent::MatchEnded { outcome } => Some(*outcome),
+            _ => None,
+        }
+    });
+    assert_eq!(outcome, Some(MatchOutcome::MonsterSlain));
+    assert_eq!(game.outcome, Some(MatchOutcome::MonsterSlain));
+    assert!(game.turn.is_none());
+}
+
+#[test]
+fn a_match_ends_only_once() {
+    let size = BoardSize::default();
+    let (mut game, mut rng) = started_match(size, open_board(size));
+    game.players[1].health = 0;
+    game.end_turn(0, &mut rng).expect("turn ended");
+
+    // A seat resuming after the end gets no turn, so nothing can end the match again
+    game.turn_index = 1;
+    assert!(game.begin_turn().is_empty());
+    let result = game.play(1, &ClientToServer::EndTurn, &mut rng);
+
+    assert_eq!(result.unwrap_err(), RejectReason::MatchOver);
+    assert_eq!(game.outcome, Some(MatchOutcome::BabyLost));
+}
+
+#[test]
+fn ending_a_turn_passes_it_on_and_refills_the_hand() {
+    let size = BoardSize::default();
+    let (mut game, mut rng) = started_match(size, open_board(size));
+    let hand_index = select(&mut game, CardEffect::MoveOneTile);
+    game.confirm_card(0, hand_index, &mut rng).expect("confirmed");
+
+    assert_eq!(game.end_turn(1, &mut rng).unwrap_err(), RejectReason::NotYourTurn);
+
+    let events = game.end_turn(0, &mut rng).expect("turn ended");
+
+    assert!(matches!(events.last(), Some(MatchEvent::TurnStarted { player_index: 1 })));
+    assert_eq!(game.turn.as_ref().map(|turn| turn.player_index), Some(1));
+    assert!(game.players[0].hand.iter().all(|card| !card.is_dummy()));
+    assert_eq!(game.stats[0].turns_taken, 1);
+}
+
+#[test]
+fn ending_a_turn_confirms_the_card_in_play() {
+    let size = BoardSize::default();
+    let (mut game, mut rng) = started_match(size, open_board(size));
+    select(&mut game, CardEffect::MoveOneTile);
+    game.move_player(0, (1, 0), false).expect("moved");
+
+    let events = game.end_turn(0, &mut rng).expect("turn ended");
+
+    assert!(matches!(events[0], MatchEvent::CardConfirmed { player_index: 0, .. }));
+    assert_eq!(game.players[0].original_position, (1, 0));
+}
+
+#[test]
+fn losing_a_baby_ends_the_match() {
+    let size = BoardSize::default();
+    let (mut game, mut rng) = started_match(size, open_board(size));
+    game.players[1].health = 0;
+
+    let events = game.end_turn(0, &mut rng).expect("turn ended");
+
+    assert!(
+        matches!(events.last(), Some(MatchEvent::MatchEnded { outcome: MatchOutcome::BabyLost }))
+    );
+    assert!(game.is_over());
+    assert!(game.turn.is_none());
+}
+
+#[test]
+fn the_babies_escape_once_all_living_ones_are_on_the_exit() {
+    let size = BoardSize::default();
+    let mut tiles = open_board(size);
+    tiles[size.index(1, 0)].is_exit = true;
+    let (mut game, _) = started_match(size, tiles);
+
+    game.players[0].position = (1, 0);
+    assert!(!game.has_escaped());
+    game.players[1].position = (1, 0);
+    assert!(game.has_escaped());
+    game.players[1].position = (4, 4);
+    game.players[1].health = 0;
+    assert!(game.has_escaped());
+}
+
+#[test]
+fn confirming_a_move_onto_the_exit_last_wins_the_match() {
+    let size = BoardSize::default();
+    let mut tiles = open_board(size);
+    tiles[size.index(1, 0)].is_exit = true;
+    let (mut game, mut rng) = started_match(size, tiles);
+    game.players[1].position = (1, 0);
+    let hand_index = select(&mut game, CardEffect::MoveOneTile);
+
+    let events = game.move_player(0, (1, 0), false).expect("moved");
+    assert!(!events.iter().any(|event| matches!(event, MatchEvent::MatchEnded { .. })));
+    assert!(!game.is_over());
+
+    let events = game.confirm_card(0, hand_index, &mut rng).expect("confirmed");
+
+    assert!(
+        matches!(events.last(), Some(MatchEvent::MatchEnded { outcome: MatchOutcome::Escaped }))
+    );
+    assert_eq!(game.outcome, Some(MatchOutcome::Escaped));
+}
+
+#[test]
+fn a_cancelled_move_onto_the_exit_does_not_win() {
+    let size = BoardSize::default();
+    let mut tiles = open_board(size);
+    tiles[size.index(1, 0)].is_exit = true;
+    let (mut game, _) = started_match(size, tiles);
+    game.players[1].position = (1, 0);
+    let hand_index = select(&mut game, CardEffect::MoveOneTile);
+    game.move_player(0, (1, 0), false).expect("moved");
+
+    game.cancel_card(0, hand_index).expect("cancelled");
+
+    assert!(!game.is_over());
+}
+
+#[test]
+fn ending_the_turn_on_a_swap_under_the_exit_wins_the_match() {
+    let size = BoardSize::default();
+    let mut tiles = open_board(size);
+    tiles[size.index(1, 0)].is_exit = true;
+    let (mut game, mut rng) = started_match(size, tiles);
+    game.players[1].position = (0, 0);
+    select(&mut game, CardEffect::SwapCard);
+    game.swap_tiles(0, size.index(0, 0), size.index(1, 0)).expect("swapped");
+    assert!(!game.is_over());
+
+    let events = game.end_turn(0, &mut rng).expect("turn ended");
+
+    assert!(
+        matches!(events.last(), Some(MatchEvent::MatchEnded { outcome: MatchOutcome::Escaped }))
+    );
+    assert!(game.turn.is_none());
+}
+
+#[test]
+fn lava_burns_and_fountains_heal_at_the_end_of_a_turn() {
+    let size = BoardSize::default();
+    let mut tiles = open_board(size);
+    tiles[size.index(0, 0)].kind = TileKind::Lava;
+    tiles[size.index(4, 4)].kind = TileKind::Fountain;
+    let (mut game, mut rng) = started_match(size, tiles);
+    game.players[1].health = PLAYER_HEALTH - FOUNTAIN_HEAL;
+
+    let events = game.end_turn(0, &mut rng).expect("turn ended");
+    assert!(events.iter().any(|event| matches!(event, MatchEvent::PlayerBurned { .. })));
+    assert_eq!(game.players[0].health, PLAYER_HEALTH - LAVA_DAMAGE);
+
+    let events = game.end_turn(1, &mut rng).expect("turn ended");
+    assert!(events.iter().any(|event| matches!(event, MatchEvent::PlayerHealed { .. })));
+}
+
+#[test]
+fn portals_send_the_player_to_their_pair() {
+    let size = BoardSize::default();
+    let mut tiles = open_board(size);
+    tiles[size.index(1, 0)].kind = TileKind::Portal { pair: 0 };
+    tiles[size.index(3, 3)].kind = TileKind::Portal { pair: 0 };
+    let (mut game, _) = started_match(size, tiles);
+    select(&mut game, CardEffect::MoveOneTile);
+
+    let events = game.move_player(0, (1, 0), false).expect("moved");
+
+    let teleported = events.iter().any(|event| {
+        matches!(event, MatchEvent::PlayerTeleported { new_position: (3, 3), .. })
+    });
+    assert!(teleported);
+    assert_eq!(game.players[0].position, (3, 3));
+}
+
+#[test]
+fn a_confirmed_move_into_treasure_finds_a_card() {
+    let size = BoardSize::default();
+    let mut tiles = open_board(size);
+    let treasure_tile = size.index(1, 0);
+    tiles[treasure_tile].kind = TileKind::Treasure;
+    let (mut game, mut rng) = started_match(size, tiles);
+    let owned = cards_owned(&game, 0);
+    let draw_count = game.decks[0].draw_count();
+    let hand_index = select(&mut game, CardEffect::MoveOneTile);
+    game.move_player(0, (1, 0), false).expect("moved");
+
+    let events = game.confirm_card(0, hand_index, &mut rng).expect("confirmed");
+
+    assert!(events.iter().any(|event| matches!(event, MatchEvent::TreasureFound { .. })));
+    assert!(!game.players[0].hand[hand_index].is_dummy());
+    assert_eq!(game.players[0].hand[hand_index].hand_index, Some(hand_index));
+    assert_eq!(game.tiles[treasure_tile].kind, TileKind::Corridor);
+    // The card comes out of the player's own deck, so they own no more cards than before
+    assert_eq!(cards_owned(&game, 0), owned);
+    assert_eq!(game.players[0].draw_pile_count, draw_count - 1);
+}
+
+#[test]
+fn ending_the_turn_on_a_move_into_treasure_finds_a_card() {
+    let size = BoardSize::default();
+    let mut tiles = open_board(size);
+    let treasure_tile = size.index(1, 0);
+    tiles[treasure_tile].kind = TileKind::Treasure;
+    let (mut game, mut rng) = started_match(size, tiles);
+    select(&mut game, CardEffect::MoveOneTile);
+    game.move_player(0, (1, 0), false).expect("moved");
+
+    let events = game.end_turn(0, &mut rng).expect("turn ended");
+
+    assert!(events.iter().any(|event| matches!(event, MatchEvent::TreasureFound { .. })));
+    assert_eq!(game.tiles[treasure_tile].kind, TileKind::Corridor);
+}
+
+#[test]
+fn pausing_returns_the_card_in_play() {
+    let size = BoardSize::default();
+    let (mut game, _) = started_match(size, open_board(size));
+    let hand_index = select(&mut game, CardEffect::MoveOneTile);
+    game.move_player(0, (1, 0), false).expect("moved");
+
+    game.pause();
+
+    assert!(game.turn.is_none());
+    assert_eq!(game.players[0].hand[hand_index].id, 1);
+    assert_eq!(game.players[0].position, (0, 0));
+    assert_eq!(game.begin_turn().len(), 1);
+    assert_eq!(game.turn.as_ref().map(|turn| turn.player_index), Some(0));
+}
+
+#[test]
+fn the_same_seed_gives_the_same_numbers() {
+    let mut first = SeededRng::new(SEED);
+    let mut second = SeededRng::new(SEED);
+    let mut other = SeededRng::new(SEED + 1);
+
+    let first: Vec<u32> = (0..16).map(|_| first.next_u32()).collect();
+    let second: Vec<u32> = (0..16).map(|_| second.next_u32()).collect();
+    let other: Vec<u32> = (0..16).map(|_| other.next_u32()).collect();
+
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+}
+
+#[test]
+fn the_same_seed_deals_the_same_match() {
+    let deal = |seed| {
+        let mut rng = SeededRng::new(seed);
+        let mut game = Match::new(2, BoardSize::new(7, 5), &mut rng);
+        game.start(&mut rng);
+        borsh::to_vec(&game).expect("encoded")
+    };
+
+    assert_eq!(deal(SEED), deal(SEED));
+    assert_ne!(deal(SEED), deal(SEED + 1));
+}
+
+#[test]
+fn picks_stay_in_range() {
+    let mut rng = SeededRng::new(SEED);
+    assert!((0..1000).all(|_| rng.pick(3) < 3));
+    assert!((0..1000).all(|_| rng.next_f32() < 1.0));
+}
+
+#[test]
+fn everyone_gets_the_same_daily_seed_on_the_same_day() {
+    assert_eq!(daily_seed(20_000), daily_seed(20_000));
+    assert_ne!(daily_seed(20_000), daily_seed(20_001));
+}
+
+#[test]
+fn playing_a_replay_back_gives_the_same_match() {
+    let mut rng = SeededRng::new(SEED);
+    let mut game = Match::new(2, BoardSize::default(), &mut rng);
+    game.start(&mut rng);
+    let mut replay = Replay::new(&game, &rng, &["red".to_string(), "blue".to_string()]);
+    game.begin_turn();
+    let actions = [
+        (0, ClientToServer::SelectCard { hand_index: 0 }),
+        (0, ClientToServer::ConfirmCard { hand_index: 0 }),
+        (0, ClientToServer::EndTurn),
+        (1, ClientToServer::EndTurn),
+        (0, ClientToServer::EndTurn),
+    ];
+    for (player_index, action) in actions {
+        game.play(player_index, &action, &mut rng).expect("played");
+        replay.steps.push(ReplayStep::Action { player_index, action });
+    }
+
+    let replay: Replay = borsh::from_slice(&replay.to_borsh()).expect("decoded");
+    let mut replay_rng = replay.rng.clone();
+    let mut replayed = replay.start.clone();
+    replayed.begin_turn();
+    for step in &replay.steps {
+        if let ReplayStep::Action { player_index, action } = step {
+            replayed.play(*player_index, action, &mut replay_rng).expect("played again");
+        }
+    }
+
+    assert_eq!(replay.seed(), SEED);
+    assert_eq!(borsh::to_vec(&replayed).ok(), borsh::to_vec(&game).ok());
+}
+
+#[test]
+fn board_positions_on_a_wide_board() {
+    let size = BoardSize::new(7, 5);
+
+    assert_eq!(size.tile_count(), 35);
+    assert_eq!(size.index(6, 0), 6);
+    assert_eq!(size.index(0, 1), 7);
+    assert_eq!(size.position(34), (6, 4));
+    for index in 0..size.tile_count() {
+        let (x, y) = size.position(index);
+        assert_eq!(size.index(x, y), index);
+    }
+    assert!(size.contains((6, 4)));
+    assert!(!size.contains((7, 0)));
+    assert!(!size.contains((0, 5)));
+}
+
+#[test]
+fn neighbours_stay_on_a_tall_board() {
+    let size = BoardSize::new(5, 7);
+    let bottom_right = size.index(4, 6);
+
+    assert_eq!(size.index_in_direction(bottom_right, Direction::Right), None);
+    assert_eq!(size.index_in_direction(bottom_right, Direction::Down), None);
+    assert_eq!(size.index_in_direction(bottom_right, Direction::Up), Some(size.index(4, 5)));
+    assert_eq!(size.index_in_direction(bottom_right, Direction::Left), Some(size.index(3, 6)));
+    assert_eq!(size.index_in_direction(size.index(4, 0), Direction::Right), None);
+    assert_eq!(size.center(), (2, 3));
+}
+
+#[test]
+fn a_wide_board_puts_everyone_on_it() {
+    let size = BoardSize::new(7, 5);
+    let mut rng = SeededRng::new(SEED);
+    let mut game = Match::new(4, size, &mut rng);
+    game.start(&mut rng);
+
+    assert_eq!(game.tiles.len(), size.tile_count());
+    assert_eq!(game.players[1].position, (6, 4));
+    assert_eq!(game.players[2].position, (6, 0));
+    assert_eq!(game.players[3].position, (0, 4));
+    assert_eq!(game.monster.as_ref().map(|monster| monster.position), Some((3, 2)));
+}
+
+#[test]
+fn decks_reshuffle_the_discard_pile_when_drawn_out() {
+    let mut rng = SeededRng::new(SEED);
+    let mut deck = Deck::new(&mut rng);
+    let deck_size = DECK_COPIES * 4;
+    assert_eq!(deck.draw_count(), deck_size);
+
+    let drawn: Vec<Card> = (0..deck_size).filter_map(|_| deck.draw(&mut rng)).collect();
+    assert_eq!(drawn.len(), deck_size);
+    assert!(deck.draw(&mut rng).is_none());
+
+    for card in drawn.into_iter().take(2) {
+        deck.discard(card);
+    }
+    assert_eq!(deck.discard_count(), 2);
+
+    let card = deck.draw(&mut rng).expect("reshuffled discard");
+    assert_eq!(card.hand_index, None);
+    assert_eq!(deck.draw_count(), 1);
+    assert_eq!(deck.discard_count(), 0);
+}
+
+#[test]
+fn played_cards_come_back_through_the_discard_pile() {
+    let size = BoardSize::default();
+    let mut rng = SeededRng::new(SEED);
+    let mut game = Match::new(2, size, &mut rng);
+    game.tiles = open_board(size);
+    game.start(&mut rng);
+    game.begin_turn();
+    assert_eq!(game.players[0].draw_pile_count, DECK_COPIES * 4 - HAND_SIZE);
+
+    // Play out the whole deck, a card and a turn for each seat at a time
+    for _ in 0..DECK_COPIES * 4 {
+        for player_index in 0..2 {
+            let hand_index = game.players[player_index].hand
+                .iter()
+                .position(|card| !card.is_dummy() && card.effect != CardEffect::FireCard);
+            if let Some(hand_index) = hand_index {
+                let select = ClientToServer::SelectCard { hand_index };
+                game.play(player_index, &select, &mut rng).expect("selected");
+                let confirm = ClientToServer::ConfirmCard { hand_index };
+                game.play(player_index, &confirm, &mut rng).expect("confirmed");
+            }
+            game.players[player_index].health = PLAYER_HEALTH;
+            game.play(player_index, &ClientToServer::EndTurn, &mut rng).expect("turn ended");
+        }
+    }
+
+    let player = &game.players[0];
+    assert!(player.hand.iter().all(|card| !card.is_dummy()));
+    let piles = player.draw_pile_count + player.discard_pile_count;
+    assert_eq!(piles + HAND_SIZE, DECK_COPIES * 4);
+    assert_eq!(player.discard_pile_count, game.decks[0].discard_count());
+}
@@ -1,7 +1,12 @@
 // Scene and mode enums for the game
+use crate::protocol::DailyResult;
+use crate::rules::MatchOutcome;
+
 #[derive(
     Debug,
     Clone,
+    Copy,
+    PartialEq,
     borsh::BorshDeserialize,
     borsh::BorshSerialize,
     serde::Serialize,
@@ -9,6 +14,7 @@
 )]
 pub enum GameMode {
     Multiplayer,
+    Daily, // Everyone plays the same dungeon that day, and the result is recorded
 }
 
 #[derive(
@@ -21,9 +27,16 @@ pub enum GameMode {
 )]
 pub enum Scene {
     Menu,
+    Lobby, // In a room, waiting for every seat to be filled and ready
     Game,
     GameOver {
         winner_ids: Vec<String>,
         loser_ids: Vec<String>,
+        outcome: MatchOutcome,
+    },
+    Replay, // Playing back the room's last match
+    DailyResults {
+        day: u64, // Days since the Unix epoch, in UTC
+        results: Vec<DailyResult>,
     },
 }
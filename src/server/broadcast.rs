@@ -1,9 +1,24 @@
 use turbo::*;
 use borsh::BorshSerialize;
 use serde::Serialize;
-use crate::server::{ ServerToClient, CurrentTurn };
-use crate::game::map::{ Tile, Player, Monster };
-use crate::game::cards::card::Card;
+use crate::protocol::{
+    ClientToServer,
+    ServerToClient,
+    RejectReason,
+    LobbyToClient,
+    RoomInfo,
+    JoinRejectReason,
+    IncompatibleReason,
+    DailyResult,
+    GameEvent,
+    BoardChange,
+    BoardSnapshot,
+};
+use crate::server::GameChannel;
+use crate::rules::Replay;
+use std::collections::HashMap;
+use crate::game::map::PlayerId;
+use crate::game::constants::PROTOCOL_VERSION;
 
 pub fn broadcast_generic<T: Serialize + BorshSerialize>(msg: T) {
     if let Err(e) = os::server::channel::broadcast(msg) {
@@ -11,117 +26,118 @@ pub fn broadcast_generic<T: Serialize + BorshSerialize>(msg: T) {
     }
 }
 
-pub fn broadcast_reset_game() {
-    broadcast_generic(ServerToClient::ResetGame);
+pub fn send_generic<T: Serialize + BorshSerialize>(user_id: &str, msg: T) {
+    if let Err(e) = os::server::channel::send(user_id, msg) {
+        log!("[GameChannel] Error sending message to {user_id}: {e}");
+    }
 }
 
-pub fn broadcast_turn(
+pub fn broadcast_connected_users(
     players: &[String],
-    current_turn_index: usize,
-    current_turn: &mut Option<CurrentTurn>,
-    board_tiles: &[Tile],
-    board_players: &[Player],
-    board_monster: &Option<Monster>
+    spectators: &[String],
+    reconnecting: &[String],
+    ready: &[String]
 ) {
-    if let Some(user_id) = players.get(current_turn_index) {
-        // Update current_turn with the new player
-        *current_turn = Some(CurrentTurn {
-            player_id: user_id.clone(),
-            selected_card: None,
-            selected_card_index: 0,
-        });
-        broadcast_board_state(board_tiles, board_players, board_monster, current_turn);
-    }
+    broadcast_generic(ServerToClient::ConnectedUsers {
+        users: players.to_vec(),
+        spectators: spectators.to_vec(),
+        reconnecting: reconnecting.to_vec(),
+        ready: ready.to_vec(),
+    });
 }
 
-pub fn broadcast_board_state(
-    board_tiles: &[Tile],
-    board_players: &[Player],
-    board_monster: &Option<Monster>,
-    current_turn: &Option<CurrentTurn>
-) {
-    broadcast_generic(ServerToClient::BoardState {
-        tiles: board_tiles.to_vec(),
-        players: board_players.to_vec(),
-        monster: board_monster.clone(),
-        current_turn: current_turn.clone(),
+pub fn broadcast_seat_assignment(player_ids: &HashMap<String, PlayerId>) {
+    broadcast_generic(ServerToClient::SeatAssignment {
+        player_ids: player_ids.clone(),
     });
 }
 
-pub fn broadcast_card_cancelled(card: &Card, player_id: &str, board_tiles: &[Tile]) {
-    broadcast_generic(ServerToClient::CardCancelled {
-        card: card.clone(),
-        player_id: player_id.to_string(),
-        board_tiles: board_tiles.to_vec(),
-    });
+pub fn broadcast_start_game() {
+    broadcast_generic(ServerToClient::StartGame);
 }
 
-pub fn broadcast_card_confirmed(card: &Card, player_id: &str) {
-    broadcast_generic(ServerToClient::CardConfirmed {
-        card: card.clone(),
-        player_id: player_id.to_string(),
-    });
+pub fn send_start_game(user_id: &str) {
+    send_generic(user_id, ServerToClient::StartGame);
 }
 
-pub fn broadcast_tile_rotation(tile_index: usize, tile: &Tile, player_id: &str) {
-    broadcast_generic(ServerToClient::TileRotated {
-        tile_index,
-        tile: tile.clone(),
-        player_id: player_id.to_string(),
-    });
+pub fn send_hello_result(user_id: &str, result: Result<(), IncompatibleReason>) {
+    let msg = match result {
+        Ok(()) => ServerToClient::HelloAccepted { protocol_version: PROTOCOL_VERSION },
+        Err(reason) => ServerToClient::Incompatible { protocol_version: PROTOCOL_VERSION, reason },
+    };
+    send_generic(user_id, msg);
 }
 
-pub fn broadcast_player_moved(player_id: &str, new_position: (usize, usize), is_canceled: bool) {
-    broadcast_generic(ServerToClient::PlayerMoved {
-        player_id: player_id.to_string(),
-        new_position,
-        is_canceled,
-    });
+/// Sends an event to everyone in the room; `GameChannel::broadcast_event` numbers it
+pub fn broadcast_event(seq: u64, event: GameEvent) {
+    broadcast_generic(ServerToClient::Event { seq, event });
 }
 
-pub fn broadcast_tiles_swapped(tile_index_1: usize, tile_index_2: usize) {
-    broadcast_generic(ServerToClient::TilesSwapped {
-        tile_index_1,
-        tile_index_2,
-    });
+pub fn send_event(user_id: &str, seq: u64, event: GameEvent) {
+    send_generic(user_id, ServerToClient::Event { seq, event });
 }
 
-pub fn broadcast_fireball_shot(
-    player_id: &str,
-    target_tile: usize,
-    direction: &crate::game::map::tile::Direction
-) {
-    broadcast_generic(ServerToClient::FireballShot {
-        player_id: player_id.to_string(),
-        tile_index: target_tile,
-        direction: direction.clone(),
-    });
+pub fn broadcast_reset_game(channel: &mut GameChannel) {
+    channel.broadcast_event(GameEvent::ResetGame);
 }
 
-pub fn broadcast_fireball_hit_result(
-    shooter_id: &str,
-    target_player_id: &str,
-    damage_dealt: &u32,
-    monster_damage: Option<u32>
-) {
-    broadcast_generic(ServerToClient::FireballHit {
-        player_id: shooter_id.to_string(),
-        target_id: target_player_id.to_string(),
-        damage_dealt: *damage_dealt,
-        monster_damage,
+pub fn send_board_snapshot(user_id: &str, seq: u64, board: &BoardSnapshot) {
+    send_generic(user_id, ServerToClient::BoardState {
+        seq,
+        seed: board.seed,
+        board_size: board.board_size,
+        tiles: board.tiles.clone(),
+        players: board.players.clone(),
+        monster: board.monster.clone(),
+        current_turn: board.current_turn.clone(),
     });
 }
 
-pub fn broadcast_player_damage_from_monster(player_id: &str, damage_dealt: u32) {
-    broadcast_generic(ServerToClient::PlayerDamageFromMonster {
-        player_id: player_id.to_string(),
-        damage_dealt,
+pub fn send_board_delta(user_id: &str, seq: u64, changes: &[BoardChange]) {
+    send_event(user_id, seq, GameEvent::BoardDelta {
+        changes: changes.to_vec(),
     });
 }
 
-pub fn broadcast_game_over(winner_ids: &[String], loser_ids: &[String]) {
-    broadcast_generic(ServerToClient::GameOver {
-        winner_ids: winner_ids.to_vec(),
-        loser_ids: loser_ids.to_vec(),
+pub fn send_action_rejected(user_id: &str, reason: RejectReason, request: ClientToServer) {
+    send_generic(user_id, ServerToClient::ActionRejected { reason, request });
+}
+
+pub fn send_replay(user_id: &str, replay: &Replay) {
+    send_generic(user_id, ServerToClient::Replay { replay: replay.clone() });
+}
+
+pub fn broadcast_room_list(rooms: &[RoomInfo]) {
+    broadcast_generic(LobbyToClient::RoomList { rooms: rooms.to_vec() });
+}
+
+pub fn send_lobby_hello_result(user_id: &str, result: Result<(), IncompatibleReason>) {
+    let msg = match result {
+        Ok(()) => LobbyToClient::HelloAccepted { protocol_version: PROTOCOL_VERSION },
+        Err(reason) => LobbyToClient::Incompatible { protocol_version: PROTOCOL_VERSION, reason },
+    };
+    send_generic(user_id, msg);
+}
+
+pub fn send_room_list(user_id: &str, rooms: &[RoomInfo]) {
+    send_generic(user_id, LobbyToClient::RoomList { rooms: rooms.to_vec() });
+}
+
+pub fn send_room_joined(user_id: &str, room: &RoomInfo) {
+    send_generic(user_id, LobbyToClient::RoomJoined {
+        room_id: room.room_id.clone(),
+        is_private: room.is_private,
+        player_count: room.player_count,
+        mode: room.mode,
+        board_size: room.board_size,
     });
 }
+
+pub fn send_daily_results(user_id: &str, day: u64, results: &[DailyResult]) {
+    send_generic(user_id, LobbyToClient::DailyResults { day, results: results.to_vec() });
+}
+
+pub fn send_join_rejected(user_id: &str, room_id: &str, reason: JoinRejectReason) {
+    log!("[LobbyChannel] {} could not join room {}: {:?}", user_id, room_id, reason);
+    send_generic(user_id, LobbyToClient::JoinRejected { room_id: room_id.to_string(), reason });
+}
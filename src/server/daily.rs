@@ -0,0 +1,41 @@
+use turbo::*;
+use crate::server::GameChannel;
+use crate::protocol::DailyResult;
+use crate::game::constants::MS_PER_DAY;
+
+/// Today as days since the Unix epoch, in UTC
+pub fn today() -> u64 {
+    time::now() / MS_PER_DAY
+}
+
+fn results_path(day: u64) -> String {
+    format!("daily/{}", day)
+}
+
+/// The results recorded for a day, empty until someone finishes that day's dungeon
+pub fn load_daily_results(day: u64) -> Vec<DailyResult> {
+    os::server::fs::read(&results_path(day)).unwrap_or_default()
+}
+
+/// Records the result of every seated user once a daily match is over. Only a user's first
+/// result of the day counts, so replaying the same dungeon can't improve it.
+pub fn record_daily_match(channel: &GameChannel, players_won: bool) {
+    let mut results = load_daily_results(channel.day);
+    for (index, user_id) in channel.players.iter().enumerate() {
+        let already_played = results.iter().any(|result| result.user_id == *user_id);
+        if user_id.is_empty() || already_played {
+            continue;
+        }
+        let stats = channel.game.stats.get(index).cloned().unwrap_or_default();
+        results.push(DailyResult {
+            user_id: user_id.clone(),
+            won: players_won,
+            turns_taken: stats.turns_taken,
+            damage_taken: stats.damage_taken,
+        });
+    }
+
+    if let Err(err) = os::server::fs::write(&results_path(channel.day), &results) {
+        log!("[GameChannel] Could not record the results of day {}: {}", channel.day, err);
+    }
+}
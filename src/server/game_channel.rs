@@ -1,23 +1,51 @@
 use turbo::*;
+use std::collections::HashMap;
 use serde::{ Serialize, Deserialize };
-use crate::game::map::{ Tile, Player, PlayerId, Monster };
-use crate::game::constants::{ MAP_SIZE, HAND_SIZE };
-use crate::game::map::board::random_tiles;
-use crate::network::ClientToServer;
+use crate::game::map::{ PlayerId, BoardSize };
+use crate::game::constants::{ MIN_PLAYERS, RECONNECT_GRACE_MS, RECONNECT_CHECK_INTERVAL_MS };
+use crate::rules::{ Match, SeededRng, Replay, ReplayStep, daily_seed };
+use crate::scene::GameMode;
+use crate::server::daily::today;
+use crate::protocol::{ ClientToServer, ServerToClient, GameEvent, BoardSnapshot };
+use crate::protocol::board_sync::{ diff_board, changes_for_viewer };
 use crate::game::cards::card::Card;
-use crate::server::broadcast::{ broadcast_generic, broadcast_turn, broadcast_board_state };
+use crate::server::broadcast::{
+    broadcast_connected_users,
+    broadcast_seat_assignment,
+    broadcast_start_game,
+    broadcast_event,
+    send_event,
+    send_board_snapshot,
+    send_board_delta,
+    send_start_game,
+};
 use crate::server::handlers::*;
 
 #[turbo::os::channel(program = "server", name = "game")]
 pub struct GameChannel {
-    pub players: Vec<String>,
-    pub current_turn_index: usize,
-    pub current_turn: Option<CurrentTurn>,
-    pub board_tiles: Vec<Tile>,
-    pub board_players: Vec<Player>,
-    pub board_monster: Option<Monster>,
+    pub players: Vec<String>, // One user per seat; a seat left mid-match holds an empty string
+    pub spectators: Vec<String>, // Connected users without a seat, they only receive broadcasts
+    pub reserved_seats: Vec<ReservedSeat>, // Seats held for players who dropped mid-match
+    pub ready: Vec<String>, // Seated users who readied up in the room lobby
+    pub player_count: usize, // Players needed to start, set by ConfigureMatch
+    pub mode: GameMode, // Set by ConfigureMatch along with the player count
+    pub board_size: BoardSize, // Set by ConfigureMatch, used for every match dealt in the room
+    pub day: u64, // Day the daily dungeon was dealt for, in daily mode
+    pub game: Match, // Board and turn state; seat i plays game.players[i]
+    pub rng: SeededRng, // Reseeded for every new match, all of the match's randomness comes from it
+    pub replay: Option<Replay>, // The current or last match, recorded from its first turn
+    pub event_seq: u64, // Sequence number of the last event sent to the clients
+    pub synced_board: BoardSnapshot, // The board as of the last sync, deltas are diffed against it
 }
 
+/// A seat kept for a disconnected player until they come back or the grace period runs out
+#[derive(borsh::BorshSerialize, borsh::BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+pub struct ReservedSeat {
+    pub user_id: String,
+    pub expires_at: u64, // ms since the Unix epoch
+}
+
+/// The turn as clients see it, with the player's user id
 #[derive(borsh::BorshSerialize, borsh::BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
 pub struct CurrentTurn {
     pub player_id: String,
@@ -26,135 +54,344 @@ pub struct CurrentTurn {
 }
 
 impl os::server::channel::ChannelHandler for GameChannel {
-    type Send = crate::server::ServerToClient;
+    type Send = ServerToClient;
     type Recv = ClientToServer;
 
     fn new() -> Self {
-        let board_players = vec![
-            Player::new(PlayerId::Player1, 0, 0, HAND_SIZE, false),
-            Player::new(PlayerId::Player2, MAP_SIZE - 1, MAP_SIZE - 1, HAND_SIZE, false)
-        ];
-        let board_tiles = random_tiles(MAP_SIZE * MAP_SIZE);
-
+        let mut rng = SeededRng::new(random::u64());
         Self {
             players: Vec::new(),
-            current_turn_index: 0,
-            current_turn: None,
-            board_tiles,
-            board_players,
-            board_monster: None,
+            spectators: Vec::new(),
+            reserved_seats: Vec::new(),
+            ready: Vec::new(),
+            player_count: MIN_PLAYERS,
+            mode: GameMode::Multiplayer,
+            board_size: BoardSize::default(),
+            day: 0,
+            game: Match::new(MIN_PLAYERS, BoardSize::default(), &mut rng),
+            rng,
+            replay: None,
+            event_seq: 0,
+            synced_board: BoardSnapshot::default(),
         }
     }
 
+    fn on_open(
+        &mut self,
+        settings: &mut os::server::channel::ChannelSettings
+    ) -> Result<(), std::io::Error> {
+        // Reserved seats are released from on_interval
+        settings.set_interval(RECONNECT_CHECK_INTERVAL_MS);
+        Ok(())
+    }
+
     fn on_connect(&mut self, user_id: &str) -> Result<(), std::io::Error> {
-        if !self.players.contains(&user_id.to_string()) {
-            self.players.push(user_id.to_string());
+        let is_connected =
+            self.players.contains(&user_id.to_string()) ||
+            self.spectators.contains(&user_id.to_string());
+        // Users beyond the seated players watch the match instead of joining the turn order
+        if !is_connected && !self.take_seat(user_id) {
+            self.spectators.push(user_id.to_string());
         }
-        broadcast_generic(crate::server::ServerToClient::ConnectedUsers {
-            users: self.players.clone(),
-        });
 
-        // Only start the game when we have 2 players
-        if self.players.len() == 2 {
-            self.current_turn_index = 0;
+        // Anyone arriving after the start skips the room lobby
+        if self.game.has_started() {
+            send_start_game(user_id);
+        }
 
-            // Spawn the monster in the center of the board
-            self.board_monster = Some(Monster::new());
+        // A returning player still owns their seat and baby
+        let was_reserved = self.reserved_seats.iter().any(|seat| seat.user_id == user_id);
+        if was_reserved {
+            log!("[GameChannel] {} reconnected to their seat", user_id);
+            self.reserved_seats.retain(|seat| seat.user_id != user_id);
+        }
+        // The current board tells the client which event comes next
+        self.send_board_snapshot(user_id);
+        self.broadcast_roster();
 
-            // Give initial hands to both players
-            let player_ids: Vec<String> = self.players.clone();
-            for player_id in player_ids {
-                crate::server::handlers::give_player_new_hand(self, &player_id);
-            }
+        self.start_if_ready();
+        Ok(())
+    }
+
+    fn on_disconnect(&mut self, user_id: &str) -> Result<(), std::io::Error> {
+        self.spectators.retain(|s| s != user_id);
+        self.ready.retain(|r| r != user_id);
+        let is_seated = self.players.iter().any(|p| p == user_id);
+        if is_seated && self.game.has_started() {
+            // Hold the seat mid-match so the player can pick up where they left off
+            self.reserved_seats.push(ReservedSeat {
+                user_id: user_id.to_string(),
+                expires_at: time::now() + RECONNECT_GRACE_MS,
+            });
+        } else if let Some(seat) = self.players.iter_mut().find(|p| *p == user_id) {
+            // Keep the seat, and the player's baby on the board, for whoever takes it over
+            seat.clear();
+        }
+        self.broadcast_roster();
 
-            broadcast_turn(
-                &self.players,
-                self.current_turn_index,
-                &mut self.current_turn,
-                &self.board_tiles,
-                &self.board_players,
-                &self.board_monster
-            );
+        if self.connected_count() < self.player_count && self.game.turn.is_some() {
+            // Game pauses until the seat is filled again
+            let events = self.game.pause();
+            self.record_step(ReplayStep::Paused);
+            broadcast_match_events(self, events);
+            self.broadcast_board_delta();
         }
         Ok(())
     }
 
-    fn on_disconnect(&mut self, user_id: &str) -> Result<(), std::io::Error> {
-        let was_turn = self.players.get(self.current_turn_index) == Some(&user_id.to_string());
-        self.players.retain(|p| p != user_id);
-        broadcast_generic(crate::server::ServerToClient::ConnectedUsers {
-            users: self.players.clone(),
-        });
+    fn on_interval(&mut self) -> Result<(), std::io::Error> {
+        let now = time::now();
+        let expired: Vec<String> = self.reserved_seats
+            .iter()
+            .filter(|seat| seat.expires_at <= now)
+            .map(|seat| seat.user_id.clone())
+            .collect();
+        if expired.is_empty() {
+            return Ok(());
+        }
 
-        if self.players.len() < 2 {
-            // Game stops if we have less than 2 players
-            self.current_turn = None;
-            broadcast_board_state(
-                &self.board_tiles,
-                &self.board_players,
-                &self.board_monster,
-                &None
-            );
-        } else {
-            // Continue game with remaining players
-            if was_turn {
-                self.current_turn_index %= self.players.len();
-            }
-            broadcast_turn(
-                &self.players,
-                self.current_turn_index,
-                &mut self.current_turn,
-                &self.board_tiles,
-                &self.board_players,
-                &self.board_monster
-            );
+        // The grace period is over, open the seats up for spectators
+        log!("[GameChannel] Releasing seats held for {:?}", expired);
+        self.reserved_seats.retain(|seat| seat.expires_at > now);
+        for seat in self.players.iter_mut().filter(|p| expired.contains(p)) {
+            seat.clear();
         }
+        self.broadcast_roster();
         Ok(())
     }
 
     fn on_data(&mut self, user_id: &str, data: Self::Recv) -> Result<(), std::io::Error> {
-        match data {
-            ClientToServer::ResetGame => {
-                handle_reset_game(self);
-            }
-            ClientToServer::EndTurn => {
-                handle_end_turn(self, user_id);
-            }
-            ClientToServer::SelectCard { hand_index } => {
-                handle_select_card(self, user_id, hand_index);
-            }
-            ClientToServer::CancelSelectCard { hand_index } => {
-                handle_cancel_select_card(self, user_id, hand_index);
-            }
-            ClientToServer::ConfirmCard { card } => {
-                handle_confirm_card(self, user_id, card);
-            }
-            ClientToServer::RotateTile { tile_index } => {
-                handle_rotate_tile(self, user_id, tile_index);
-            }
-            ClientToServer::MovePlayer { new_position, is_canceled } => {
-                handle_move_player(self, user_id, new_position, is_canceled);
-            }
-            ClientToServer::SwapTiles { tile_index_1, tile_index_2 } => {
-                handle_swap_tiles(self, tile_index_1, tile_index_2);
-            }
-            ClientToServer::FireballShot { target_tile, direction } => {
-                handle_fireball_shot(self, user_id, target_tile, direction);
-            }
-            ClientToServer::FireballHit { shooter_id, hit_tile_index: from_tile_index } => {
-                handle_fireball_hit(self, &shooter_id, from_tile_index);
+        let result = authorize_action(self, user_id, &data).and_then(|_| {
+            match data.clone() {
+                ClientToServer::Hello { protocol_version, client_build } => {
+                    handle_hello(user_id, protocol_version, &client_build);
+                    Ok(())
+                }
+                ClientToServer::ResetGame { seed } => {
+                    handle_reset_game(self, seed);
+                    Ok(())
+                }
+                ClientToServer::ConfigureMatch { player_count, mode, board_size } => {
+                    handle_configure_match(self, player_count, mode, board_size);
+                    Ok(())
+                }
+                ClientToServer::TakeSeat => handle_take_seat(self, user_id),
+                ClientToServer::RequestSnapshot => {
+                    self.send_board_snapshot(user_id);
+                    Ok(())
+                }
+                ClientToServer::SetReady { ready } => {
+                    handle_set_ready(self, user_id, ready);
+                    Ok(())
+                }
+                ClientToServer::RequestReplay => handle_request_replay(self, user_id),
+                ClientToServer::EndTurn |
+                ClientToServer::SelectCard { .. } |
+                ClientToServer::CancelSelectCard { .. } |
+                ClientToServer::ConfirmCard { .. } |
+                ClientToServer::RotateTile { .. } |
+                ClientToServer::MovePlayer { .. } |
+                ClientToServer::SwapTiles { .. } |
+                ClientToServer::FireballShot { .. } => handle_turn_action(self, user_id, &data),
             }
+        });
+
+        if let Err(reason) = result {
+            reject_action(self, user_id, data, reason);
         }
         Ok(())
     }
 }
 
 impl GameChannel {
-    /// Get the user ID for a given PlayerId enum
-    pub fn get_user_id(&self, player_id: &PlayerId) -> Option<&String> {
-        match player_id {
-            PlayerId::Player1 => self.players.get(0),
-            PlayerId::Player2 => self.players.get(1),
+    /// The user playing from a seat, empty if nobody holds it
+    pub fn seat_user(&self, player_index: usize) -> String {
+        self.players.get(player_index).cloned().unwrap_or_default()
+    }
+
+    /// Number of seats that are taken, including seats held for a reconnecting player
+    pub fn seated_count(&self) -> usize {
+        self.players
+            .iter()
+            .filter(|p| !p.is_empty())
+            .count()
+    }
+
+    /// Number of seats whose player is currently connected
+    pub fn connected_count(&self) -> usize {
+        self.players
+            .iter()
+            .filter(|p| !p.is_empty())
+            .filter(|p| !self.reserved_seats.iter().any(|seat| seat.user_id == **p))
+            .count()
+    }
+
+    /// Users whose seat is being held while they reconnect
+    pub fn reconnecting_users(&self) -> Vec<String> {
+        self.reserved_seats
+            .iter()
+            .map(|seat| seat.user_id.clone())
+            .collect()
+    }
+
+    /// Which baby each seated user controls, by seat index
+    pub fn player_ids(&self) -> HashMap<String, PlayerId> {
+        self.players
+            .iter()
+            .enumerate()
+            .filter(|(_, user_id)| !user_id.is_empty())
+            .filter_map(|(index, user_id)| {
+                PlayerId::from_index(index).map(|player_id| (user_id.clone(), player_id))
+            })
+            .collect()
+    }
+
+    /// Tells every client who is in the room and which baby each seated user controls.
+    /// Sent whenever the seats, spectators or ready flags change.
+    pub fn broadcast_roster(&self) {
+        broadcast_connected_users(
+            &self.players,
+            &self.spectators,
+            &self.reconnecting_users(),
+            &self.ready
+        );
+        broadcast_seat_assignment(&self.player_ids());
+    }
+
+    /// Returns true once every seated user has readied up
+    pub fn all_ready(&self) -> bool {
+        self.players
+            .iter()
+            .filter(|p| !p.is_empty())
+            .all(|p| self.ready.contains(p))
+    }
+
+    /// Puts a user in the first empty seat, returns false if every seat is taken
+    pub fn take_seat(&mut self, user_id: &str) -> bool {
+        if let Some(seat) = self.players.iter_mut().find(|p| p.is_empty()) {
+            *seat = user_id.to_string();
+            return true;
+        }
+        if self.players.len() < self.player_count {
+            self.players.push(user_id.to_string());
+            return true;
+        }
+        false
+    }
+
+    /// Starts the match once every seat is taken and ready, or resumes a match paused by a
+    /// disconnect
+    pub fn start_if_ready(&mut self) {
+        if self.connected_count() != self.player_count || self.game.turn.is_some() {
+            return;
+        }
+
+        // A paused match keeps its monster, hands and turn order
+        let is_new_match = !self.game.has_started();
+        if is_new_match {
+            if !self.all_ready() {
+                return;
+            }
+            self.start_match();
+            broadcast_start_game();
+        }
+
+        if self.game.begin_turn().is_empty() {
+            return;
+        }
+        if is_new_match {
+            self.broadcast_board_snapshot();
+        } else {
+            self.broadcast_board_delta();
+        }
+    }
+
+    /// Deals a new board from a seed. The same seed, board size and number of seats always give
+    /// the same match, as long as the players make the same moves.
+    pub fn new_match(&mut self, seed: u64) {
+        self.rng = SeededRng::new(seed);
+        self.game = Match::new(self.player_count, self.board_size, &mut self.rng);
+    }
+
+    /// Spawns the monster and deals the hands, and starts recording the match from here
+    pub fn start_match(&mut self) {
+        self.game.start(&mut self.rng);
+        self.replay = Some(Replay::new(&self.game, &self.rng, &self.players));
+    }
+
+    /// Adds a step to the replay of the match being played
+    pub fn record_step(&mut self, step: ReplayStep) {
+        if let Some(replay) = &mut self.replay {
+            replay.steps.push(step);
         }
     }
+
+    /// Deals today's daily dungeon, the same board in every room playing it
+    pub fn deal_daily(&mut self) {
+        self.day = today();
+        self.board_size = BoardSize::default();
+        self.new_match(daily_seed(self.day));
+    }
+
+    /// The board as it is right now
+    pub fn board_snapshot(&self) -> BoardSnapshot {
+        BoardSnapshot::of_match(&self.game, self.rng.seed(), &self.players)
+    }
+
+    /// Connected users with the seat they play from; spectators have none.
+    /// Board updates are sent to each of them separately so hands stay private.
+    pub fn viewers(&self) -> Vec<(String, Option<usize>)> {
+        let reconnecting = self.reconnecting_users();
+        let seated = self.players
+            .iter()
+            .enumerate()
+            .filter(|(_, user_id)| !user_id.is_empty() && !reconnecting.contains(user_id))
+            .map(|(index, user_id)| (user_id.clone(), Some(index)));
+        let watching = self.spectators.iter().map(|user_id| (user_id.clone(), None));
+        seated.chain(watching).collect()
+    }
+
+    /// Numbers an event and sends it to everyone in the room. An event with cards from a hand
+    /// goes to each viewer separately, with only what they may see.
+    pub fn broadcast_event(&mut self, event: GameEvent) {
+        self.event_seq += 1;
+        if !event.is_private() {
+            broadcast_event(self.event_seq, event);
+            return;
+        }
+        for (user_id, _) in self.viewers() {
+            send_event(&user_id, self.event_seq, event.for_viewer(&user_id));
+        }
+    }
+
+    /// Sends everyone what changed on the board since the last sync
+    pub fn broadcast_board_delta(&mut self) {
+        let board = self.board_snapshot();
+        let changes = diff_board(&self.synced_board, &board);
+        self.event_seq += 1;
+        for (user_id, viewer_index) in self.viewers() {
+            let changes = changes_for_viewer(&changes, viewer_index);
+            send_board_delta(&user_id, self.event_seq, &changes);
+        }
+        self.synced_board = board;
+    }
+
+    /// Sends everyone the whole board, for a new match
+    pub fn broadcast_board_snapshot(&mut self) {
+        self.event_seq += 1;
+        self.synced_board = self.board_snapshot();
+        for (user_id, viewer_index) in self.viewers() {
+            let board = self.synced_board.for_viewer(viewer_index);
+            send_board_snapshot(&user_id, self.event_seq, &board);
+        }
+    }
+
+    /// Sends one user the whole board, when they arrive or missed an event.
+    /// Changes in later deltas carry their resulting values, so sending the live board
+    /// rather than the synced one is safe.
+    pub fn send_board_snapshot(&self, user_id: &str) {
+        let viewer_index = self.players.iter().position(|p| p == user_id);
+        let board = self.board_snapshot().for_viewer(viewer_index);
+        send_board_snapshot(user_id, self.event_seq, &board);
+    }
 }
@@ -1,433 +1,280 @@
 use turbo::*;
-use crate::game::cards::card_effect::CardEffect;
-use crate::server::{ GameChannel, CurrentTurn };
-use crate::PlayerId;
+use crate::server::GameChannel;
+use crate::protocol::{ ClientToServer, GameEvent, RejectReason, check_protocol_version };
+use crate::rules::{ MatchEvent, ReplayStep };
 use crate::server::broadcast::{
-    broadcast_board_state,
-    broadcast_card_cancelled,
-    broadcast_card_confirmed,
-    broadcast_tile_rotation,
-    broadcast_player_moved,
-    broadcast_tiles_swapped,
-    broadcast_fireball_shot,
-    broadcast_fireball_hit_result,
-    broadcast_game_over,
     broadcast_reset_game,
+    send_action_rejected,
+    send_hello_result,
+    send_replay,
 };
-use crate::game::cards::card::Card;
-use crate::game::constants::{ DEBUG_MODE, HAND_SIZE, FIREBALL_DAMAGE, MAP_SIZE };
-use crate::game::map::player::Player;
-use crate::game::map::board::random_tiles;
-use crate::game::map::monster::Monster;
+use crate::server::daily::record_daily_match;
+use crate::server::replays::save_replay;
+use crate::scene::GameMode;
+use crate::game::map::BoardSize;
+use crate::game::constants::{ MIN_PLAYERS, MAX_PLAYERS };
 
 /// Helper function to get the player index for a given user_id
 fn get_player_index(channel: &GameChannel, user_id: &str) -> Option<usize> {
     channel.players.iter().position(|p| p == user_id)
 }
 
-/// Helper function to get a mutable reference to the player for a given user_id
-fn get_player_mut<'a>(
-    channel: &'a mut GameChannel,
-    user_id: &str
-) -> Option<&'a mut crate::game::map::Player> {
-    let player_index = get_player_index(channel, user_id)?;
-    channel.board_players.get_mut(player_index)
-}
-
-/// Helper function to give a player a new hand of random cards
-pub fn give_player_new_hand(channel: &mut GameChannel, user_id: &str) {
-    if let Some(player) = get_player_mut(channel, user_id) {
-        let mut new_hand = Player::new_hand(HAND_SIZE, false);
-
-        for (i, card) in new_hand.iter_mut().enumerate() {
-            card.hand_index = Some(i);
-        }
-
-        player.hand = new_hand;
-    }
-}
-
-pub fn handle_reset_game(channel: &mut GameChannel) {
-    log!("🚀 [HANDLE] Resetting game...");
-
-    channel.board_tiles = random_tiles(MAP_SIZE * MAP_SIZE);
-    channel.board_players = vec![
-        Player::new(PlayerId::Player1, 0, 0, HAND_SIZE, false),
-        Player::new(PlayerId::Player2, MAP_SIZE - 1, MAP_SIZE - 1, HAND_SIZE, false)
-    ];
-    channel.board_monster = Some(Monster::new());
-    channel.current_turn_index = 0;
-    channel.current_turn = None;
-
-    let player_ids: Vec<String> = channel.players.clone();
-    for player_id in &player_ids {
-        give_player_new_hand(channel, player_id);
-    }
-
-    // Start the game by broadcasting reset and then the first turn
-    if channel.players.len() == 2 {
-        broadcast_reset_game();
-
-        // Set up the first turn
-        if let Some(user_id) = channel.players.get(channel.current_turn_index) {
-            channel.current_turn = Some(CurrentTurn {
-                player_id: user_id.clone(),
-                selected_card: None,
-                selected_card_index: 0,
-            });
-        }
-
-        // Broadcast the new board state
-        broadcast_board_state(
-            &channel.board_tiles,
-            &channel.board_players,
-            &channel.board_monster,
-            &channel.current_turn
-        );
-    }
-}
-
-pub fn handle_end_turn(channel: &mut GameChannel, user_id: &str) {
-    if let Some(turn) = &channel.current_turn {
-        if turn.player_id == user_id && turn.selected_card.is_some() {
-            if let Some(selected_card) = &turn.selected_card {
-                handle_confirm_card(channel, user_id, selected_card.clone());
+/// Central gate for the room's own requests: seats, readiness, resets and match setup.
+/// Turn actions only need a seat here; whose turn it is and what the card in play allows
+/// is checked by the match itself.
+pub fn authorize_action(
+    channel: &GameChannel,
+    user_id: &str,
+    action: &ClientToServer
+) -> Result<(), RejectReason> {
+    match action {
+        // Anyone may say hello, including users the server will turn away
+        ClientToServer::Hello { .. } |
+        ClientToServer::RequestSnapshot |
+        ClientToServer::RequestReplay => Ok(()),
+        ClientToServer::TakeSeat => {
+            if !channel.spectators.iter().any(|s| s == user_id) {
+                return Err(RejectReason::NotSeated);
             }
+            if channel.seated_count() >= channel.player_count {
+                return Err(RejectReason::NoEmptySeat);
+            }
+            Ok(())
         }
-    }
-
-    give_player_new_hand(channel, user_id);
-
-    // Monster takes its turn after player
-    if let Some(monster) = &mut channel.board_monster {
-        monster.take_turn(&mut channel.board_players, &channel.board_tiles);
-    }
-
-    // check for game over
-    for player in &channel.board_players {
-        if player.health <= 0 {
-            broadcast_game_over(&[], &channel.players); // Empty winners, all players are losers
-            return; // Exit early since game is over
-        }
-    }
-
-    channel.current_turn_index = (channel.current_turn_index + 1) % channel.players.len();
-
-    if let Some(next_user_id) = channel.players.get(channel.current_turn_index) {
-        channel.current_turn = Some(CurrentTurn {
-            player_id: next_user_id.clone(),
-            selected_card: None,
-            selected_card_index: 0,
-        });
-
-        broadcast_board_state(
-            &channel.board_tiles,
-            &channel.board_players,
-            &channel.board_monster,
-            &channel.current_turn
-        );
-    }
-}
-
-pub fn handle_select_card(channel: &mut GameChannel, user_id: &str, hand_index: usize) {
-    // Get the player and their selected card
-    let (_player, selected_card) = if let Some(player) = get_player_mut(channel, user_id) {
-        if hand_index < player.hand.len() {
-            let card = player.hand[hand_index].clone();
-            // Replace the selected card with a dummy card
-            player.hand[hand_index] = Card::dummy_card();
-            (player, card)
-        } else {
-            log!("[GameChannel] Invalid hand_index: {} for player {}", hand_index, user_id);
-            return;
-        }
-    } else {
-        log!("[GameChannel] Could not find player for user_id: {}", user_id);
-        return;
-    };
-
-    channel.current_turn = Some(CurrentTurn {
-        player_id: user_id.to_string(),
-        selected_card: Some(selected_card.clone()),
-        selected_card_index: hand_index,
-    });
-}
-
-pub fn handle_cancel_select_card(channel: &mut GameChannel, user_id: &str, hand_index: usize) {
-    // Get the card from the current turn to handle cancellation logic
-    let card = if let Some(turn) = &channel.current_turn {
-        if let Some(selected_card) = &turn.selected_card {
-            selected_card.clone()
-        } else {
-            log!("[GameChannel] No card selected for cancel");
-            return;
-        }
-    } else {
-        log!("[GameChannel] No current turn for cancel");
-        return;
-    };
-
-    // Handle card-specific cancellation logic
-    match card.effect {
-        CardEffect::RotateCard => {
-            // Revert all tiles to their original rotation in the server's board state
-            for tile in channel.board_tiles.iter_mut() {
-                tile.rotate_entrances(tile.original_rotation);
-                tile.current_rotation = tile.original_rotation;
+        ClientToServer::ResetGame { .. } => {
+            if get_player_index(channel, user_id).is_none() {
+                return Err(RejectReason::NotSeated);
+            }
+            // Before the first match the room lobby decides when to start
+            if !channel.game.has_started() {
+                return Err(RejectReason::MatchNotStarted);
             }
-            log!("[GameChannel] Reverted all tiles to original rotation for RotateCard cancel");
+            Ok(())
         }
-        CardEffect::SwapCard => {
-            // Revert all tiles to their original positions by rebuilding the array
-            let mut tiles_with_positions: Vec<_> = channel.board_tiles
-                .iter()
-                .enumerate()
-                .map(|(_current_index, tile)| (tile.original_location, tile.clone()))
-                .collect();
-
-            // Sort by original_location to get tiles back in order
-            tiles_with_positions.sort_by_key(|(original_pos, _)| *original_pos);
-
-            // Extract just the tiles in the correct order
-            channel.board_tiles = tiles_with_positions
-                .into_iter()
-                .map(|(_, tile)| tile)
-                .collect();
-
-            log!("[GameChannel] Reverted all tiles to original positions for SwapCard cancel");
+        ClientToServer::SetReady { .. } => {
+            if get_player_index(channel, user_id).is_none() {
+                return Err(RejectReason::NotSeated);
+            }
+            if channel.game.has_started() {
+                return Err(RejectReason::GameInProgress);
+            }
+            Ok(())
         }
-        CardEffect::MoveOneTile => {
-            // Move the player back to their original position in the server's board state
-            if let Some(player) = get_player_mut(channel, user_id) {
-                player.position = player.original_position;
-                log!(
-                    "[GameChannel] Moved player back to original position {:?} for MoveOneTile cancel",
-                    player.original_position
-                );
+        ClientToServer::ConfigureMatch { player_count, mode, board_size } => {
+            // Everyone in the room sends the settings they were given by the lobby
+            let is_current =
+                *player_count == channel.player_count &&
+                *mode == channel.mode &&
+                *board_size == channel.board_size;
+            if is_current {
+                return Ok(());
+            }
+            if get_player_index(channel, user_id).is_none() {
+                return Err(RejectReason::NotSeated);
+            }
+            // A match paused by a disconnect has no turn, but its seats are still held
+            if channel.game.has_started() {
+                return Err(RejectReason::GameInProgress);
             }
+            if !(MIN_PLAYERS..=MAX_PLAYERS).contains(player_count) {
+                return Err(RejectReason::InvalidPlayerCount);
+            }
+            if channel.players.len() > *player_count {
+                return Err(RejectReason::InvalidPlayerCount);
+            }
+            if !board_size.is_valid() {
+                return Err(RejectReason::InvalidBoardSize);
+            }
+            Ok(())
         }
         _ => {
-            // For other card types, no special handling needed
+            // Spectators never have the turn
+            if get_player_index(channel, user_id).is_none() {
+                return Err(RejectReason::NotYourTurn);
+            }
+            Ok(())
         }
     }
+}
 
-    // Restore the original card back to the player's hand
-    if let Some(player) = get_player_mut(channel, user_id) {
-        if hand_index < player.hand.len() {
-            player.hand[hand_index] = card.clone();
-        }
-    }
+/// Tells the sender why their action failed, then resyncs them with the server's board so
+/// any optimistic local changes are undone.
+pub fn reject_action(
+    channel: &GameChannel,
+    user_id: &str,
+    action: ClientToServer,
+    reason: RejectReason
+) {
+    log!("[GameChannel] Rejected {:?} from {}: {:?}", action, user_id, reason);
 
-    // Update the current turn state
-    channel.current_turn = Some(CurrentTurn {
-        player_id: user_id.to_string(),
-        selected_card: None,
-        selected_card_index: hand_index,
-    });
+    send_action_rejected(user_id, reason, action);
+    channel.send_board_snapshot(user_id);
+}
 
-    broadcast_card_cancelled(&card, user_id, &channel.board_tiles);
+/// Plays a turn action on the match, records it for the replay and broadcasts what came of it
+pub fn handle_turn_action(
+    channel: &mut GameChannel,
+    user_id: &str,
+    action: &ClientToServer
+) -> Result<(), RejectReason> {
+    let player_index = get_player_index(channel, user_id).ok_or(RejectReason::NotYourTurn)?;
+    let events = channel.game.play(player_index, action, &mut channel.rng)?;
+    channel.record_step(ReplayStep::Action { player_index, action: action.clone() });
+    broadcast_match_events(channel, events);
+    Ok(())
 }
 
-pub fn handle_confirm_card(channel: &mut GameChannel, user_id: &str, card: Card) {
-    match card.effect {
-        CardEffect::MoveOneTile => {
-            if let Some(player) = get_player_mut(channel, user_id) {
-                player.update_original_position();
-            }
-        }
-        CardEffect::RotateCard => {
-            for tile in channel.board_tiles.iter_mut() {
-                tile.original_rotation = tile.current_rotation;
+/// Passes what happened in the match on to the clients, with seats turned into user ids
+pub fn broadcast_match_events(channel: &mut GameChannel, events: Vec<MatchEvent>) {
+    for event in events {
+        match &event {
+            MatchEvent::TurnStarted { player_index } => {
+                log!("[GameChannel] Turn of {}", channel.seat_user(*player_index));
+                channel.broadcast_board_delta();
             }
-        }
-        CardEffect::SwapCard => {
-            for (current_index, tile) in channel.board_tiles.iter_mut().enumerate() {
-                tile.original_location = current_index;
+            MatchEvent::PlayerMoved { player_index, new_position, .. } => {
+                let user_id = channel.seat_user(*player_index);
+                log!("[GameChannel] Moved {} to {:?}", user_id, new_position);
             }
-        }
-        _ => {
-            // Do nothing for other card types
-        }
-    }
-
-    // Replace the confirmed card in the player's hand with a dummy card
-    // Use the card's hand_index instead of the turn's selected_card_index
-    if let Some(hand_index) = card.hand_index {
-        if let Some(player) = get_player_mut(channel, user_id) {
-            if hand_index < player.hand.len() {
-                player.hand[hand_index] = Card::dummy_card();
+            MatchEvent::FireballShot { tile_index, direction, end_tile_index, .. } => {
                 log!(
-                    "[GameChannel] Replaced card at hand index {} with dummy card for player {}",
-                    hand_index,
-                    user_id
+                    "[GameChannel] Fireball from tile {} going {:?} stops at tile {}",
+                    tile_index,
+                    direction,
+                    end_tile_index
                 );
             }
+            MatchEvent::DoorUnlocked { player_index, tile_index, direction } => {
+                let user_id = channel.seat_user(*player_index);
+                log!("[GameChannel] {} unlocked {:?} of tile {}", user_id, direction, tile_index);
+            }
+            MatchEvent::PlayerTeleported { player_index, new_position } => {
+                let user_id = channel.seat_user(*player_index);
+                log!("[GameChannel] {} came out of a portal at {:?}", user_id, new_position);
+            }
+            MatchEvent::FireballHit { target, damage, .. } => {
+                log!("[GameChannel] Fireball hit {:?} for {} damage", target, damage);
+            }
+            MatchEvent::MatchEnded { outcome } => {
+                log!("[GameChannel] Match over: {:?}", outcome);
+                if channel.mode == GameMode::Daily {
+                    record_daily_match(channel, outcome.players_won());
+                }
+                if let Some(replay) = &channel.replay {
+                    save_replay(replay);
+                }
+            }
+            _ => {}
         }
-    }
-
-    broadcast_card_confirmed(&card, user_id);
 
-    // Clear the current turn's selected card
-    if let Some(turn) = &mut channel.current_turn {
-        if turn.player_id == user_id {
-            turn.selected_card = None;
+        if let Some(game_event) = GameEvent::from_match_event(event, &channel.players) {
+            channel.broadcast_event(game_event);
         }
     }
 }
 
-pub fn handle_rotate_tile(channel: &mut GameChannel, user_id: &str, tile_index: usize) {
-    if let Some(tile) = channel.board_tiles.get_mut(tile_index) {
-        let new_rotation = (tile.current_rotation + 1) % 4;
-        tile.rotate_entrances(new_rotation);
-    }
-
-    if let Some(tile) = channel.board_tiles.get(tile_index) {
-        broadcast_tile_rotation(tile_index, tile, user_id);
-    }
-}
-
-pub fn handle_move_player(
+pub fn handle_configure_match(
     channel: &mut GameChannel,
-    user_id: &str,
-    new_position: (usize, usize),
-    is_canceled: bool
+    player_count: usize,
+    mode: GameMode,
+    board_size: BoardSize
 ) {
-    if let Some(player) = get_player_mut(channel, user_id) {
-        player.position = new_position;
-        log!("[GameChannel] Updated player {:?} position to {:?}", player.id, new_position);
-    } else {
-        log!("[GameChannel] Could not find player for user_id: {}", user_id);
+    let is_current =
+        channel.player_count == player_count &&
+        channel.mode == mode &&
+        channel.board_size == board_size;
+    if is_current {
+        return;
     }
-
-    broadcast_player_moved(user_id, new_position, is_canceled);
+    log!(
+        "🚀 [HANDLE] Configuring {:?} match for {} players on a {} board",
+        mode,
+        player_count,
+        board_size
+    );
+
+    channel.player_count = player_count;
+    channel.mode = mode;
+    channel.board_size = board_size;
+    match mode {
+        GameMode::Daily => channel.deal_daily(),
+        // Dealt again from the same seed, so the seed alone still describes the board
+        GameMode::Multiplayer => channel.new_match(channel.rng.seed()),
+    }
+    channel.start_if_ready();
 }
 
-pub fn handle_swap_tiles(channel: &mut GameChannel, tile_index_1: usize, tile_index_2: usize) {
-    // Validate that the tiles are within bounds
-    if tile_index_1 >= channel.board_tiles.len() || tile_index_2 >= channel.board_tiles.len() {
-        log!(
-            "[GameChannel] Invalid tile indices for swap: {} or {} out of bounds",
-            tile_index_1,
-            tile_index_2
-        );
-        return;
+/// Answers a client's Hello with whether its protocol version can talk to this server
+pub fn handle_hello(user_id: &str, protocol_version: u32, client_build: &str) {
+    let result = check_protocol_version(protocol_version);
+    match result {
+        Ok(()) => log!("🚀 [HANDLE] {} said hello from build {}", user_id, client_build),
+        Err(reason) =>
+            log!(
+                "🚀 [HANDLE] {} is incompatible ({:?}), build {} speaks v{}",
+                user_id,
+                reason,
+                client_build,
+                protocol_version
+            ),
     }
-
-    channel.board_tiles.swap(tile_index_1, tile_index_2);
-    broadcast_tiles_swapped(tile_index_1, tile_index_2);
+    send_hello_result(user_id, result);
 }
 
-pub fn handle_fireball_shot(
-    channel: &mut GameChannel,
-    user_id: &str,
-    target_tile: usize,
-    direction: crate::game::map::tile::Direction
-) {
-    // Check if it's the user's turn
-    if let Some(current_turn) = &channel.current_turn {
-        if current_turn.player_id != user_id {
-            log!("[GameChannel] Not {}'s turn, ignoring fireball request", user_id);
-            return;
-        }
-    } else {
-        log!("[GameChannel] No active turn, ignoring fireball request");
-        return;
+/// Moves a spectator into the seat a player left and resumes the match if it was waiting on it
+pub fn handle_take_seat(channel: &mut GameChannel, user_id: &str) -> Result<(), RejectReason> {
+    if !channel.take_seat(user_id) {
+        return Err(RejectReason::NoEmptySeat);
     }
+    log!("🚀 [HANDLE] Spectator {} took a seat", user_id);
 
-    // Validate tile index
-    if target_tile >= channel.board_tiles.len() {
-        log!("[GameChannel] Invalid tile index: {}", target_tile);
-        return;
-    }
+    channel.spectators.retain(|s| s != user_id);
+    channel.broadcast_roster();
+    channel.start_if_ready();
+    Ok(())
+}
 
-    let player = get_player_mut(channel, user_id).unwrap();
-    let player_pos = player.position;
+/// Marks a seated player as ready or not, and starts the match once every seat is ready
+pub fn handle_set_ready(channel: &mut GameChannel, user_id: &str, ready: bool) {
+    log!("🚀 [HANDLE] {} is {}", user_id, if ready { "ready" } else { "not ready" });
 
-    log!("[GameChannel] Fireball created at {:?} in direction {:?}", player_pos, direction);
-    // if not debug, confirm the card
-    if !DEBUG_MODE {
-        handle_confirm_card(channel, user_id, Card::fire_card());
+    channel.ready.retain(|r| r != user_id);
+    if ready {
+        channel.ready.push(user_id.to_string());
     }
-    broadcast_fireball_shot(user_id, target_tile, &direction);
+    channel.broadcast_roster();
+    channel.start_if_ready();
 }
 
-pub fn handle_fireball_hit(channel: &mut GameChannel, shooter_id: &str, hit_tile_index: usize) {
-    // Convert tile index to position coordinates
-    let hit_position = (hit_tile_index % 5, hit_tile_index / 5);
-
-    log!("monster position: {:?}", channel.board_monster.as_ref().unwrap().position);
-    log!("hit position: {:?}", hit_position);
-
-    // Find the monster at the hit position
-    if let Some(monster) = &mut channel.board_monster {
-        if monster.position == hit_position {
-            log!("[GameChannel] Fireball hit monster at {:?}", hit_position);
-            monster.take_damage(FIREBALL_DAMAGE);
-            log!(
-                "[GameChannel] Monster took {} damage, health now: {}",
-                FIREBALL_DAMAGE,
-                monster.health
-            );
-
-            // Check if monster is defeated
-            if monster.health <= 0 {
-                log!("[GameChannel] Monster defeated! Both players win!");
-                // Get both player user IDs for the victory
-                let player1_id = channel.players.get(0).cloned();
-                let player2_id = channel.players.get(1).cloned();
-
-                if let (Some(p1), Some(p2)) = (player1_id, player2_id) {
-                    broadcast_game_over(&[p1, p2], &[]); // Both players win, no losers
-                    return;
-                }
-            }
-
-            // Broadcast fireball hit result with monster damage
-            broadcast_fireball_hit_result(shooter_id, "monster", &0, Some(FIREBALL_DAMAGE));
-            return;
-        }
+/// Starts a new match from the given seed, or from a new one if the player did not pick one.
+/// A daily room always deals the day's dungeon again.
+pub fn handle_reset_game(channel: &mut GameChannel, seed: Option<u64>) {
+    match channel.mode {
+        GameMode::Daily => channel.deal_daily(),
+        GameMode::Multiplayer => channel.new_match(seed.unwrap_or_else(random::u64)),
     }
+    log!("🚀 [HANDLE] Resetting game with seed {}...", channel.rng.seed());
 
-    // Find the target player at the hit position
-    let (target_player_index, target_player) = match
-        channel.board_players
-            .iter()
-            .enumerate()
-            .find(|(_, player)| player.position == hit_position)
-    {
-        Some((index, player)) => (index, player),
-        None => {
-            log!("[GameChannel] No player at hit position {:?}, no damage dealt", hit_position);
-            return;
-        }
-    };
+    channel.start_match();
 
-    // Convert PlayerId enum to actual user ID
-    let target_user_id = match channel.get_user_id(&target_player.id) {
-        Some(id) => id.clone(),
-        None => {
-            log!("[GameChannel] Could not find user ID for player {:?}", target_player.id);
-            return;
-        }
-    };
+    // Start the game by broadcasting reset and then the first turn
+    if channel.connected_count() == channel.player_count {
+        broadcast_reset_game(channel);
+        channel.game.begin_turn();
 
-    // Don't damage the shooter
-    if target_user_id == shooter_id {
-        log!("[GameChannel] Fireball hit shooter, no damage dealt");
-        return;
+        // Broadcast the new board state
+        channel.broadcast_board_snapshot();
     }
+}
 
-    let damage_dealt = FIREBALL_DAMAGE;
-    if let Some(player_mut) = channel.board_players.get_mut(target_player_index) {
-        player_mut.take_damage(damage_dealt);
-        log!("[GameChannel] Player {} took {} damage from fireball", player_mut.id, damage_dealt);
-
-        // Check for game over
-        if player_mut.health <= 0 {
-            broadcast_game_over(&[], &channel.players);
-        }
+/// Sends a user the replay of the room's last match. A replay holds every hand, deck and the
+/// generator state, so it is never sent while the match it records is still being played.
+pub fn handle_request_replay(channel: &GameChannel, user_id: &str) -> Result<(), RejectReason> {
+    let replay = channel.replay.as_ref().ok_or(RejectReason::NoReplay)?;
+    if channel.game.has_started() && !channel.game.is_over() {
+        return Err(RejectReason::MatchNotOver);
     }
-
-    // Broadcast the hit result
-    broadcast_fireball_hit_result(shooter_id, &target_user_id, &damage_dealt, None);
+    log!("🚀 [HANDLE] Sending {} the replay of seed {}", user_id, replay.seed());
+    send_replay(user_id, replay);
+    Ok(())
 }
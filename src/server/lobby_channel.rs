@@ -0,0 +1,177 @@
+use turbo::*;
+use crate::protocol::{
+    ClientToLobby,
+    LobbyToClient,
+    RoomInfo,
+    JoinRejectReason,
+    check_protocol_version,
+};
+use crate::game::constants::{ ROOM_CODE_LENGTH, ROOM_CODE_CHARS, MIN_PLAYERS, MAX_PLAYERS };
+use crate::server::daily::{ today, load_daily_results };
+use crate::scene::GameMode;
+use crate::game::map::BoardSize;
+use crate::server::broadcast::{
+    broadcast_room_list,
+    send_lobby_hello_result,
+    send_room_list,
+    send_room_joined,
+    send_join_rejected,
+    send_daily_results,
+};
+
+/// Keeps track of the game rooms so clients can list, create and join them from the menu.
+/// Each room is played on its own `GameChannel`, subscribed to with the room id.
+#[turbo::os::channel(program = "server", name = "lobby")]
+pub struct LobbyChannel {
+    pub rooms: Vec<RoomInfo>,
+}
+
+impl os::server::channel::ChannelHandler for LobbyChannel {
+    type Send = LobbyToClient;
+    type Recv = ClientToLobby;
+
+    fn new() -> Self {
+        Self {
+            rooms: Vec::new(),
+        }
+    }
+
+    fn on_connect(&mut self, user_id: &str) -> Result<(), std::io::Error> {
+        send_room_list(user_id, &self.public_rooms());
+        Ok(())
+    }
+
+    fn on_disconnect(&mut self, user_id: &str) -> Result<(), std::io::Error> {
+        self.leave_room(user_id);
+        broadcast_room_list(&self.public_rooms());
+        Ok(())
+    }
+
+    fn on_data(&mut self, user_id: &str, data: Self::Recv) -> Result<(), std::io::Error> {
+        match data {
+            ClientToLobby::Hello { protocol_version, client_build } => {
+                let result = check_protocol_version(protocol_version);
+                if let Err(reason) = result {
+                    log!(
+                        "[LobbyChannel] {} is incompatible ({:?}), build {} speaks v{}",
+                        user_id,
+                        reason,
+                        client_build,
+                        protocol_version
+                    );
+                }
+                send_lobby_hello_result(user_id, result);
+            }
+            ClientToLobby::Create { is_private, player_count, mode, board_size } => {
+                self.leave_room(user_id);
+                let board_size = match mode {
+                    GameMode::Multiplayer => board_size.clamped(),
+                    GameMode::Daily => BoardSize::default(),
+                };
+                let room = RoomInfo {
+                    room_id: self.new_room_id(),
+                    players: vec![user_id.to_string()],
+                    is_private,
+                    player_count: player_count.clamp(MIN_PLAYERS, MAX_PLAYERS),
+                    mode,
+                    board_size,
+                };
+                log!("[LobbyChannel] {} created room {:?}", user_id, room);
+                send_room_joined(user_id, &room);
+                self.rooms.push(room);
+            }
+            ClientToLobby::Join { room_id } => {
+                // Codes are typed by hand, so accept them in any case
+                let room_id = room_id.to_uppercase();
+                match self.join_room(user_id, &room_id) {
+                    Ok(room) => send_room_joined(user_id, &room),
+                    Err(reason) => send_join_rejected(user_id, &room_id, reason),
+                }
+            }
+            ClientToLobby::Watch { room_id } => {
+                let room_id = room_id.to_uppercase();
+                match self.watch_room(user_id, &room_id) {
+                    Ok(room) => send_room_joined(user_id, &room),
+                    Err(reason) => send_join_rejected(user_id, &room_id, reason),
+                }
+            }
+            ClientToLobby::Leave => {
+                self.leave_room(user_id);
+            }
+            ClientToLobby::RequestDailyResults => {
+                let day = today();
+                send_daily_results(user_id, day, &load_daily_results(day));
+            }
+        }
+        broadcast_room_list(&self.public_rooms());
+        Ok(())
+    }
+}
+
+impl LobbyChannel {
+    /// Moves a user into an existing room, leaving any room they were in before.
+    /// Returns the room as it is after joining.
+    fn join_room(&mut self, user_id: &str, room_id: &str) -> Result<RoomInfo, JoinRejectReason> {
+        let mut room = self.rooms
+            .iter()
+            .find(|room| room.room_id == room_id)
+            .cloned()
+            .ok_or(JoinRejectReason::RoomNotFound)?;
+        if room.players.iter().any(|p| p == user_id) {
+            return Ok(room);
+        }
+        if room.is_full() {
+            return Err(JoinRejectReason::RoomFull);
+        }
+
+        self.leave_room(user_id);
+        room.players.push(user_id.to_string());
+        if let Some(existing) = self.rooms.iter_mut().find(|r| r.room_id == room_id) {
+            *existing = room.clone();
+        }
+        Ok(room)
+    }
+
+    /// Rooms that anyone may see in the menu; private rooms are only reachable by code
+    fn public_rooms(&self) -> Vec<RoomInfo> {
+        self.rooms
+            .iter()
+            .filter(|room| !room.is_private)
+            .cloned()
+            .collect()
+    }
+
+    /// Lets a user follow a room as a spectator; the game channel decides who gets a seat
+    fn watch_room(&mut self, user_id: &str, room_id: &str) -> Result<RoomInfo, JoinRejectReason> {
+        let room = self.rooms
+            .iter()
+            .find(|room| room.room_id == room_id)
+            .cloned()
+            .ok_or(JoinRejectReason::RoomNotFound)?;
+        if !room.players.iter().any(|p| p == user_id) {
+            self.leave_room(user_id);
+        }
+        Ok(room)
+    }
+
+    /// Removes a user from every room and drops rooms that are left empty
+    fn leave_room(&mut self, user_id: &str) {
+        for room in self.rooms.iter_mut() {
+            room.players.retain(|p| p != user_id);
+        }
+        self.rooms.retain(|room| !room.players.is_empty());
+    }
+
+    /// Picks a short, easy to read room code that is not in use yet
+    fn new_room_id(&self) -> String {
+        let chars: Vec<char> = ROOM_CODE_CHARS.chars().collect();
+        loop {
+            let room_id: String = (0..ROOM_CODE_LENGTH)
+                .map(|_| chars[(random::u32() as usize) % chars.len()])
+                .collect();
+            if !self.rooms.iter().any(|room| room.room_id == room_id) {
+                return room_id;
+            }
+        }
+    }
+}
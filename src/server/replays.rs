@@ -0,0 +1,18 @@
+use turbo::*;
+use crate::rules::Replay;
+
+/// Stores a finished match's replay twice: borsh for loading it again, JSON for reading it.
+/// Files are named after the seed and the time the match ended.
+pub fn save_replay(replay: &Replay) {
+    let path = format!("replays/{}-{}", replay.seed(), time::now());
+    let files = [
+        (format!("{}.borsh", path), replay.to_borsh()),
+        (format!("{}.json", path), replay.to_json().into_bytes()),
+    ];
+    for (file, data) in files {
+        match os::server::fs::write_bytes(&file, &data) {
+            Ok(_) => log!("[GameChannel] Saved replay {}", file),
+            Err(err) => log!("[GameChannel] Could not save replay {}: {}", file, err),
+        }
+    }
+}